@@ -0,0 +1,277 @@
+use crate::{
+    print::Printable,
+    validate::{validate, ValidationIssue},
+    view::{Node, View},
+};
+use alloc::vec::Vec;
+use bumpalo::{collections::Vec as BumpVec, Bump};
+
+#[derive(Clone, Copy, Debug)]
+/// Arena-allocated version of [`Doc`](crate::Doc).
+///
+/// It's printed in the same way as [`Doc`](crate::Doc),
+/// but nodes are allocated in a [`DocArena`] instead of individual `Rc`s and `Vec`s.
+///
+/// Docs that don't allocate can be created by functions on this enum,
+/// while others should be created by methods on [`DocArena`].
+pub enum ArenaDoc<'a> {
+    #[doc(hidden)]
+    Nil,
+
+    #[doc(hidden)]
+    Alt(&'a ArenaDoc<'a>, &'a ArenaDoc<'a>),
+
+    #[doc(hidden)]
+    Union(&'a ArenaDoc<'a>, &'a ArenaDoc<'a>),
+
+    #[doc(hidden)]
+    Nest(usize, &'a ArenaDoc<'a>),
+
+    #[doc(hidden)]
+    NestAlign(usize, &'a ArenaDoc<'a>),
+
+    #[doc(hidden)]
+    Text(&'a str),
+
+    #[doc(hidden)]
+    NewLine,
+
+    #[doc(hidden)]
+    EmptyLine,
+
+    #[doc(hidden)]
+    Break(usize, usize),
+
+    #[doc(hidden)]
+    Group(&'a [ArenaDoc<'a>]),
+
+    #[doc(hidden)]
+    List(&'a [ArenaDoc<'a>]),
+
+    #[doc(hidden)]
+    AlignAnchor(&'a str),
+
+    #[doc(hidden)]
+    AlignScope(&'a ArenaDoc<'a>),
+}
+
+impl<'a> ArenaDoc<'a> {
+    #[inline]
+    /// Empty doc, which does nothing. Same as [`Doc::nil`](crate::Doc::nil).
+    pub fn nil() -> ArenaDoc<'a> {
+        ArenaDoc::Nil
+    }
+
+    #[inline]
+    /// Just a space. Same as [`Doc::space`](crate::Doc::space).
+    pub fn space() -> ArenaDoc<'a> {
+        ArenaDoc::Text(" ")
+    }
+
+    #[inline]
+    /// Force to print a line break. Same as [`Doc::hard_line`](crate::Doc::hard_line).
+    pub fn hard_line() -> ArenaDoc<'a> {
+        ArenaDoc::NewLine
+    }
+
+    #[inline]
+    /// Same as [`Doc::soft_line`](crate::Doc::soft_line).
+    pub fn soft_line() -> ArenaDoc<'a> {
+        ArenaDoc::Group(&[ArenaDoc::Break(1, 0)])
+    }
+
+    #[inline]
+    /// Same as [`Doc::empty_line`](crate::Doc::empty_line).
+    pub fn empty_line() -> ArenaDoc<'a> {
+        ArenaDoc::EmptyLine
+    }
+
+    #[inline]
+    /// Same as [`Doc::line_or_space`](crate::Doc::line_or_space).
+    pub fn line_or_space() -> ArenaDoc<'a> {
+        ArenaDoc::Break(1, 0)
+    }
+
+    #[inline]
+    /// Same as [`Doc::line_or_nil`](crate::Doc::line_or_nil).
+    pub fn line_or_nil() -> ArenaDoc<'a> {
+        ArenaDoc::Break(0, 0)
+    }
+
+    /// Walk the whole doc tree and report structural problems.
+    /// See [`Doc::validate`](crate::Doc::validate) for details.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validate(self)
+    }
+}
+
+#[derive(Default)]
+/// Arena for allocating [`ArenaDoc`]s.
+///
+/// Docs are allocated in a few large chunks, and they're freed all at once
+/// when the arena is dropped.
+/// Methods of this struct behave the same as the corresponding functions of [`Doc`](crate::Doc).
+///
+/// ```
+/// use tiny_pretty::{print, ArenaDoc, DocArena, PrintOptions};
+///
+/// let arena = DocArena::new();
+/// let doc = arena.group(arena.list([
+///     arena.text("aaaa"),
+///     arena.nest(ArenaDoc::line_or_space(), 2),
+///     arena.text("bbbb"),
+/// ]));
+///
+/// assert_eq!("aaaa\n  bbbb", &print(&doc, &PrintOptions {
+///     width: 5,
+///     ..Default::default()
+/// }));
+/// assert_eq!("aaaa bbbb", &print(&doc, &Default::default()));
+/// ```
+pub struct DocArena {
+    bump: Bump,
+}
+
+impl DocArena {
+    /// Create an empty arena.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    /// Insert a piece of text. It **must not** contain line breaks.
+    ///
+    /// The text is borrowed, not copied. To insert a temporary string,
+    /// use [`alloc_text`](DocArena::alloc_text).
+    pub fn text<'a>(&'a self, s: &'a str) -> ArenaDoc<'a> {
+        ArenaDoc::Text(s)
+    }
+
+    #[inline]
+    /// Copy a piece of text into arena and insert it. It **must not** contain line breaks.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, DocArena};
+    ///
+    /// let arena = DocArena::new();
+    /// let doc = arena.alloc_text(&format!("{}", 42));
+    /// assert_eq!("42", &print(&doc, &Default::default()));
+    /// ```
+    pub fn alloc_text<'a>(&'a self, s: &str) -> ArenaDoc<'a> {
+        ArenaDoc::Text(self.bump.alloc_str(s))
+    }
+
+    /// Create a list of docs.
+    pub fn list<'a>(&'a self, docs: impl IntoIterator<Item = ArenaDoc<'a>>) -> ArenaDoc<'a> {
+        ArenaDoc::List(BumpVec::from_iter_in(docs, &self.bump).into_bump_slice())
+    }
+
+    #[inline]
+    /// Apply `doc_flat` when it can be put on a single line,
+    /// otherwise apply `doc_break`.
+    pub fn flat_or_break<'a>(
+        &'a self,
+        doc_flat: ArenaDoc<'a>,
+        doc_break: ArenaDoc<'a>,
+    ) -> ArenaDoc<'a> {
+        ArenaDoc::Alt(self.bump.alloc(doc_flat), self.bump.alloc(doc_break))
+    }
+
+    #[inline]
+    /// Try applying the `attempt` doc. If it exceeds the width limitation, apply the `alternate` doc.
+    pub fn union<'a>(&'a self, attempt: ArenaDoc<'a>, alternate: ArenaDoc<'a>) -> ArenaDoc<'a> {
+        ArenaDoc::Union(self.bump.alloc(attempt), self.bump.alloc(alternate))
+    }
+
+    #[inline]
+    /// Mark the docs as a group.
+    pub fn group<'a>(&'a self, doc: ArenaDoc<'a>) -> ArenaDoc<'a> {
+        match doc {
+            ArenaDoc::List(list) => ArenaDoc::Group(list),
+            ArenaDoc::Group(..) => doc,
+            doc => ArenaDoc::Group(self.bump.alloc_slice_copy(&[doc])),
+        }
+    }
+
+    /// Join two docs.
+    pub fn append<'a>(&'a self, doc: ArenaDoc<'a>, other: ArenaDoc<'a>) -> ArenaDoc<'a> {
+        self.concat(doc, [other])
+    }
+
+    /// Concatenate an iterator whose items are docs.
+    pub fn concat<'a>(
+        &'a self,
+        doc: ArenaDoc<'a>,
+        iter: impl IntoIterator<Item = ArenaDoc<'a>>,
+    ) -> ArenaDoc<'a> {
+        let mut current = BumpVec::new_in(&self.bump);
+        match doc {
+            ArenaDoc::List(docs) => current.extend_from_slice(docs),
+            doc => current.push(doc),
+        }
+        for doc in iter {
+            match doc {
+                ArenaDoc::List(docs) => current.extend_from_slice(docs),
+                doc => current.push(doc),
+            }
+        }
+        ArenaDoc::List(current.into_bump_slice())
+    }
+
+    #[inline]
+    /// Increase indentation level.
+    pub fn nest<'a>(&'a self, doc: ArenaDoc<'a>, size: usize) -> ArenaDoc<'a> {
+        if let ArenaDoc::Break(spaces, offset) = doc {
+            ArenaDoc::Break(spaces, offset + size)
+        } else {
+            ArenaDoc::Nest(size, self.bump.alloc(doc))
+        }
+    }
+
+    #[inline]
+    /// Increase indentation for alignment, which is always printed as spaces.
+    /// Same as [`Doc::nest_align`](crate::Doc::nest_align).
+    pub fn nest_align<'a>(&'a self, doc: ArenaDoc<'a>, size: usize) -> ArenaDoc<'a> {
+        ArenaDoc::NestAlign(size, self.bump.alloc(doc))
+    }
+
+    #[inline]
+    /// Insert an alignment anchor. Same as [`Doc::align_anchor`](crate::Doc::align_anchor).
+    pub fn align_anchor<'a>(&'a self, key: &'a str) -> ArenaDoc<'a> {
+        ArenaDoc::AlignAnchor(key)
+    }
+
+    #[inline]
+    /// Create an alignment scope for anchors inside.
+    /// Same as [`Doc::align_scope`](crate::Doc::align_scope).
+    pub fn align_scope<'a>(&'a self, doc: ArenaDoc<'a>) -> ArenaDoc<'a> {
+        ArenaDoc::AlignScope(self.bump.alloc(doc))
+    }
+
+    /// Get the number of bytes allocated by this arena.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+}
+
+impl View for ArenaDoc<'_> {
+    fn view(&self) -> Node<'_, Self> {
+        match *self {
+            ArenaDoc::Nil => Node::Nil,
+            ArenaDoc::Alt(doc_flat, doc_break) => Node::Alt(doc_flat, doc_break),
+            ArenaDoc::Union(attempt, alternate) => Node::Union(attempt, alternate),
+            ArenaDoc::Nest(offset, doc) => Node::Nest(offset, doc),
+            ArenaDoc::NestAlign(offset, doc) => Node::NestAlign(offset, doc),
+            ArenaDoc::Text(text) => Node::Text(text),
+            ArenaDoc::NewLine => Node::NewLine,
+            ArenaDoc::EmptyLine => Node::EmptyLine,
+            ArenaDoc::Break(spaces, offset) => Node::Break(spaces, offset),
+            ArenaDoc::Group(docs) => Node::Group(docs),
+            ArenaDoc::List(docs) => Node::List(docs),
+            ArenaDoc::AlignAnchor(key) => Node::AlignAnchor(key),
+            ArenaDoc::AlignScope(doc) => Node::AlignScope(doc),
+        }
+    }
+}
+
+impl Printable for ArenaDoc<'_> {}
@@ -0,0 +1,172 @@
+use crate::{print, Doc, PrintOptions};
+use alloc::{format, string::String, vec, vec::Vec};
+
+impl Doc<'_> {
+    /// Render the doc as Rust code of builder calls that create it.
+    ///
+    /// The derived `Debug` output of doc is hard to read,
+    /// so this is useful for debugging or pasting failing docs into regression tests.
+    /// The code is laid out by this crate itself with the given options.
+    ///
+    /// ```
+    /// use tiny_pretty::{Doc, PrintOptions};
+    ///
+    /// let doc = Doc::text("a").append(Doc::line_or_space()).group();
+    /// assert_eq!(
+    ///     r#"Doc::text("a").append(Doc::line_or_space()).group()"#,
+    ///     doc.to_builder_code(&Default::default()),
+    /// );
+    ///
+    /// let doc = Doc::list(vec![
+    ///     Doc::text("fn("),
+    ///     Doc::line_or_nil().nest(2),
+    ///     Doc::text("arg"),
+    ///     Doc::flat_or_break(Doc::nil(), Doc::text(",")),
+    ///     Doc::line_or_nil(),
+    ///     Doc::text(")"),
+    /// ]).group();
+    /// assert_eq!(r#"Doc::text("fn(")
+    ///     .append(Doc::line_or_nil().nest(2))
+    ///     .append(Doc::text("arg"))
+    ///     .append(Doc::flat_or_break(Doc::nil(), Doc::text(",")))
+    ///     .append(Doc::line_or_nil())
+    ///     .append(Doc::text(")"))
+    ///     .group()"#, doc.to_builder_code(&PrintOptions {
+    ///     width: 60,
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub fn to_builder_code(&self, options: &PrintOptions) -> String {
+        print(&build(self).into_doc(), options)
+    }
+}
+
+/// Rust expression which is either a simple expression or a chain of method calls.
+enum Code {
+    Expr(Doc<'static>),
+    Chain(Doc<'static>, Vec<Doc<'static>>),
+}
+
+impl Code {
+    fn method(self, name: &str, args: Vec<Doc<'static>>) -> Code {
+        let call = call(format!(".{name}"), args);
+        match self {
+            Code::Expr(receiver) => Code::Chain(receiver, vec![call]),
+            Code::Chain(receiver, mut calls) => {
+                calls.push(call);
+                Code::Chain(receiver, calls)
+            }
+        }
+    }
+
+    fn into_doc(self) -> Doc<'static> {
+        match self {
+            Code::Expr(doc) => doc,
+            Code::Chain(receiver, calls) => receiver.append(
+                Doc::list(
+                    calls
+                        .into_iter()
+                        .flat_map(|call| [Doc::line_or_nil(), call])
+                        .collect(),
+                )
+                .nest(4)
+                .group(),
+            ),
+        }
+    }
+}
+
+fn call(name: String, args: Vec<Doc<'static>>) -> Doc<'static> {
+    delimited(name, "(", ")", args)
+}
+
+fn delimited(
+    name: String,
+    open: &'static str,
+    close: &'static str,
+    args: Vec<Doc<'static>>,
+) -> Doc<'static> {
+    if args.is_empty() {
+        return Doc::text(format!("{name}{open}{close}"));
+    }
+    let mut docs = Vec::with_capacity(args.len() * 2);
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            docs.push(Doc::text(","));
+            docs.push(Doc::line_or_space());
+        }
+        docs.push(arg);
+    }
+    Doc::text(format!("{name}{open}"))
+        .append(
+            Doc::line_or_nil()
+                .concat(docs.into_iter())
+                .append(Doc::flat_or_break(Doc::nil(), Doc::text(",")))
+                .nest(4)
+                .append(Doc::line_or_nil())
+                .group(),
+        )
+        .append(Doc::text(close))
+}
+
+fn build(doc: &Doc) -> Code {
+    match doc {
+        Doc::Nil => Code::Expr(Doc::text("Doc::nil()")),
+        Doc::Alt(doc_flat, doc_break) => Code::Expr(call(
+            "Doc::flat_or_break".into(),
+            vec![build(doc_flat).into_doc(), build(doc_break).into_doc()],
+        )),
+        Doc::Union(attempt, alternate) => {
+            build(attempt).method("union", vec![build(alternate).into_doc()])
+        }
+        Doc::Nest(offset, doc) => build(doc).method("nest", vec![Doc::text(format!("{offset}"))]),
+        Doc::NestAlign(offset, doc) => {
+            build(doc).method("nest_align", vec![Doc::text(format!("{offset}"))])
+        }
+        Doc::Text(text) if text == " " => Code::Expr(Doc::text("Doc::space()")),
+        Doc::Text(text) => Code::Expr(Doc::text(format!("Doc::text({text:?})"))),
+        Doc::NewLine => Code::Expr(Doc::text("Doc::hard_line()")),
+        Doc::EmptyLine => Code::Expr(Doc::text("Doc::empty_line()")),
+        Doc::Break(1, 0) => Code::Expr(Doc::text("Doc::line_or_space()")),
+        Doc::Break(0, 0) => Code::Expr(Doc::text("Doc::line_or_nil()")),
+        Doc::Break(spaces @ (0 | 1), offset) => {
+            build(&Doc::Break(*spaces, 0)).method("nest", vec![Doc::text(format!("{offset}"))])
+        }
+        Doc::Break(spaces, offset) => {
+            Code::Expr(Doc::text(format!("Doc::Break({spaces}, {offset})")))
+        }
+        Doc::Group(docs) => match &docs[..] {
+            [Doc::Break(1, 0)] => Code::Expr(Doc::text("Doc::soft_line()")),
+            // `group` won't wrap lists and groups again.
+            [doc] if !matches!(doc, Doc::List(..) | Doc::Group(..)) => {
+                build(doc).method("group", vec![])
+            }
+            docs => build_list(docs).method("group", vec![]),
+        },
+        Doc::List(docs) => build_list(docs),
+        Doc::AlignAnchor(key) => Code::Expr(Doc::text(format!("Doc::align_anchor({key:?})"))),
+        Doc::AlignScope(doc) => build(doc).method("align_scope", vec![]),
+    }
+}
+
+fn build_list(docs: &[Doc]) -> Code {
+    match docs {
+        // `append` flattens lists, so it can only be used when there're no nested lists.
+        [first, rest @ ..]
+            if !rest.is_empty() && docs.iter().all(|doc| !matches!(doc, Doc::List(..))) =>
+        {
+            rest.iter().fold(build(first), |code, doc| {
+                code.method("append", vec![build(doc).into_doc()])
+            })
+        }
+        docs => Code::Expr(call(
+            "Doc::list".into(),
+            vec![delimited(
+                "vec!".into(),
+                "[",
+                "]",
+                docs.iter().map(|doc| build(doc).into_doc()).collect(),
+            )],
+        )),
+    }
+}
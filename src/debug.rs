@@ -0,0 +1,243 @@
+use crate::{
+    pretty::{braced, sequence, single_tuple},
+    print, Doc, PrintOptions,
+};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
+impl Doc<'_> {
+    /// Build doc from `Debug` output of a value.
+    ///
+    /// The output of `{:?}` is parsed with the grammar of derived `Debug` implementations,
+    /// then structs, tuples, lists and maps are put on a single line if possible;
+    /// otherwise each item is placed on its own line with indentation and trailing comma,
+    /// just like `{:#?}` does.
+    ///
+    /// If the output can't be parsed, for example, it contains unbalanced brackets
+    /// or line breaks, it will be kept as is.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// #[derive(Debug)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let doc = Doc::from_debug(&vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    /// assert_eq!(
+    ///     "[Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]",
+    ///     &print(&doc, &Default::default()),
+    /// );
+    /// assert_eq!(r#"
+    /// [
+    ///     Point { x: 1, y: 2 },
+    ///     Point { x: 3, y: 4 },
+    /// ]"#.trim(), &print(&doc, &PrintOptions {
+    ///     width: 30,
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub fn from_debug<T: fmt::Debug + ?Sized>(value: &T) -> Doc<'static> {
+        let output = format!("{value:?}");
+        if !output.contains('\n') {
+            let mut parser = Parser {
+                input: &output,
+                pos: 0,
+            };
+            if let Some((mut docs, trailing_comma)) = parser.parse_items(None)
+                && docs.len() == 1
+                && !trailing_comma
+            {
+                return docs.remove(0);
+            }
+        }
+        let mut lines = output.lines().map(|line| Doc::text(String::from(line)));
+        let first = lines.next().unwrap_or_else(Doc::nil);
+        lines.fold(first, |doc, line| doc.append(Doc::hard_line()).append(line))
+    }
+}
+
+/// Pretty print `Debug` output of a value with the given width limitation and default options.
+///
+/// It returns a wrapper that implements `Display`, so it can be used in formatting macros
+/// without allocating an extra string. See [`Doc::from_debug`] for details about the layout.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use tiny_pretty::pretty_debug;
+///
+/// let value = BTreeMap::from([("key", (1, Some("value")))]);
+/// assert_eq!(r#"{"key": (1, Some("value"))}"#, pretty_debug(&value, 80).to_string());
+/// assert_eq!(r#"
+/// {
+///     "key": (
+///         1,
+///         Some("value"),
+///     ),
+/// }"#.trim(), format!("{}", pretty_debug(&value, 24)));
+/// ```
+pub fn pretty_debug<T: fmt::Debug + ?Sized>(value: &T, width: usize) -> PrettyDebug<'_, T> {
+    PrettyDebug { value, width }
+}
+
+/// Wrapper for pretty printing `Debug` output of a value. Created by [`pretty_debug`].
+pub struct PrettyDebug<'a, T: ?Sized> {
+    value: &'a T,
+    width: usize,
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Display for PrettyDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&print(
+            &Doc::from_debug(self.value),
+            &PrintOptions {
+                width: self.width,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for PrettyDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+struct Parser<'s> {
+    input: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    /// Parse comma-separated items until the closing delimiter or the end of input.
+    /// The closing delimiter itself won't be consumed.
+    ///
+    /// Returns `None` if delimiters are unbalanced.
+    fn parse_items(&mut self, close: Option<char>) -> Option<(Vec<Doc<'static>>, bool)> {
+        let mut items = vec![];
+        self.skip_spaces();
+        if self.peek() == close {
+            return Some((items, false));
+        }
+        loop {
+            items.push(self.parse_item()?);
+            match self.peek() {
+                Some(',') => {
+                    self.pos += 1;
+                    self.skip_spaces();
+                    if self.peek() == close {
+                        return Some((items, true));
+                    }
+                }
+                c if c == close => return Some((items, false)),
+                _ => return None,
+            }
+        }
+    }
+
+    /// Parse a single item until comma or closing delimiter.
+    fn parse_item(&mut self) -> Option<Doc<'static>> {
+        let mut doc = Doc::nil();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                ',' | ')' | ']' | '}' => break,
+                '(' | '[' | '{' => {
+                    self.pos += 1;
+                    let close = match c {
+                        '(' => ')',
+                        '[' => ']',
+                        _ => '}',
+                    };
+                    // Struct fields are surrounded by spaces, while map entries aren't.
+                    let spaced = c == '{' && self.peek() == Some(' ');
+                    let (items, trailing_comma) = self.parse_items(Some(close))?;
+                    self.pos += 1;
+                    let delimited = if items.is_empty() {
+                        Doc::text(format!("{c}{close}"))
+                    } else if trailing_comma && items.len() == 1 && c == '(' {
+                        single_tuple(items.into_iter().next()?)
+                    } else if spaced {
+                        braced(open_str(c), items, close_str(close))
+                    } else {
+                        sequence(open_str(c), items, close_str(close))
+                    };
+                    doc = doc
+                        .append(Doc::text(core::mem::take(&mut text)))
+                        .append(delimited);
+                }
+                '"' => {
+                    let start = self.pos;
+                    self.pos += 1;
+                    while let Some(d) = self.bump() {
+                        if d == '\\' {
+                            self.bump();
+                        } else if d == '"' {
+                            break;
+                        }
+                    }
+                    text.push_str(&self.input[start..self.pos]);
+                }
+                '\'' => {
+                    let literal = char_literal(&self.input[self.pos..]).unwrap_or("'");
+                    self.pos += literal.len();
+                    text.push_str(literal);
+                }
+                _ => {
+                    self.pos += c.len_utf8();
+                    text.push(c);
+                }
+            }
+        }
+        let trimmed = text.trim_end().len();
+        text.truncate(trimmed);
+        Some(doc.append(Doc::text(text)).simplify())
+    }
+}
+
+/// Match a char literal like `'a'` or `'\u{1f600}'` at the start of input.
+/// Single quotes may also appear in other places, such as lifetimes in type names.
+fn char_literal(input: &str) -> Option<&str> {
+    let rest = input.strip_prefix('\'')?;
+    let len = if rest.starts_with('\\') {
+        rest.get(2..)?.find('\'')? + 2
+    } else {
+        rest.chars().next()?.len_utf8()
+    };
+    rest[len..].starts_with('\'').then(|| &input[..len + 2])
+}
+
+fn open_str(c: char) -> &'static str {
+    match c {
+        '(' => "(",
+        '[' => "[",
+        _ => "{",
+    }
+}
+
+fn close_str(c: char) -> &'static str {
+    match c {
+        ')' => ")",
+        ']' => "]",
+        _ => "}",
+    }
+}
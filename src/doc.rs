@@ -1,4 +1,13 @@
-use std::{borrow::Cow, rc::Rc};
+use crate::{
+    print::Map,
+    view::{Node, View},
+};
+#[cfg(not(feature = "sync"))]
+use alloc::rc::Rc;
+#[cfg(feature = "sync")]
+use alloc::sync::Arc as Rc;
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::ops::{Add, AddAssign};
 
 #[derive(Clone, Debug)]
 /// The data structure that describes about pretty printing.
@@ -22,6 +31,10 @@ pub enum Doc<'a> {
     #[doc(hidden)]
     Nest(usize, Rc<Doc<'a>>),
 
+    #[doc(hidden)]
+    /// Like `Nest`, but the offset is always printed as spaces.
+    NestAlign(usize, Rc<Doc<'a>>),
+
     #[doc(hidden)]
     Text(Cow<'a, str>),
 
@@ -41,6 +54,14 @@ pub enum Doc<'a> {
 
     #[doc(hidden)]
     List(Vec<Doc<'a>>),
+
+    #[doc(hidden)]
+    /// Padded with spaces to the rightmost column reached by anchors
+    /// with the same key in the enclosing alignment scope.
+    AlignAnchor(Cow<'a, str>),
+
+    #[doc(hidden)]
+    AlignScope(Rc<Doc<'a>>),
 }
 
 impl<'a> Doc<'a> {
@@ -511,4 +532,553 @@ impl<'a> Doc<'a> {
             Doc::Nest(size, Rc::new(self))
         }
     }
+
+    #[inline]
+    /// Increase indentation for alignment. Usually this method should be called on group
+    /// or line break. Calling this on text won't take any effects.
+    ///
+    /// Unlike [`nest`](Doc::nest), the offset is always printed as spaces,
+    /// even if [`indent_kind`](crate::PrintOptions::indent_kind) is [`Tab`](crate::IndentKind::Tab).
+    /// Indentation levels before alignment are still printed as tabs,
+    /// but anything after alignment is printed as spaces,
+    /// so the output looks the same at any tab width.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, IndentKind, PrintOptions};
+    ///
+    /// let options = PrintOptions {
+    ///     indent_kind: IndentKind::Tab,
+    ///     tab_size: 4,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let doc = Doc::text("foo(a,")
+    ///     .append(Doc::hard_line().append(Doc::text("b)")).nest_align(4))
+    ///     .nest(4);
+    /// assert_eq!("foo(a,\n\t    b)", &print(&doc, &options));
+    ///
+    /// // Compared with `nest`:
+    /// let doc = Doc::text("foo(a,")
+    ///     .append(Doc::hard_line().append(Doc::text("b)")).nest(4))
+    ///     .nest(4);
+    /// assert_eq!("foo(a,\n\t\tb)", &print(&doc, &options));
+    ///
+    /// // Indentation after alignment is printed as spaces.
+    /// let doc = Doc::hard_line().nest(4).nest_align(2).nest(4);
+    /// assert_eq!("\n\t      ", &print(&doc, &options));
+    /// ```
+    pub fn nest_align(self, size: usize) -> Doc<'a> {
+        Doc::NestAlign(size, Rc::new(self))
+    }
+
+    #[inline]
+    /// Insert an alignment anchor.
+    ///
+    /// Within an [alignment scope](Doc::align_scope), every anchor with the same key
+    /// is padded with spaces to the rightmost column that any of them reaches.
+    /// If there're multiple anchors with the same key on a line, only the first one is aligned.
+    /// Anchors outside any alignment scope won't take any effects.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let binding = |name, value| {
+    ///     Doc::text("let ")
+    ///         .append(Doc::text(name))
+    ///         .append(Doc::align_anchor("="))
+    ///         .append(Doc::text(" = "))
+    ///         .append(Doc::text(value))
+    ///         .append(Doc::text(";"))
+    /// };
+    /// let doc = Doc::join(
+    ///     vec![binding("a", "1"), binding("long_name", "2"), binding("bc", "3")],
+    ///     Doc::hard_line(),
+    /// )
+    /// .align_scope();
+    /// assert_eq!(r#"
+    /// let a         = 1;
+    /// let long_name = 2;
+    /// let bc        = 3;"#.trim(), &print(&doc, &Default::default()));
+    ///
+    /// let doc = Doc::text("a").append(Doc::align_anchor("=")).append(Doc::text(" = 1"));
+    /// assert_eq!("a = 1", &print(&doc, &Default::default()));
+    /// ```
+    pub fn align_anchor(key: impl Into<Cow<'a, str>>) -> Doc<'a> {
+        Doc::AlignAnchor(key.into())
+    }
+
+    #[inline]
+    /// Create an alignment scope for [anchors](Doc::align_anchor) inside.
+    ///
+    /// Anchors belong to the innermost scope, so anchors in nested scopes
+    /// are aligned separately from the outer ones.
+    /// Different keys are aligned independently, even on the same line.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let field = |name, ty, value| {
+    ///     Doc::text(name)
+    ///         .append(Doc::text(":"))
+    ///         .append(Doc::align_anchor("type"))
+    ///         .append(Doc::text(" "))
+    ///         .append(Doc::text(ty))
+    ///         .append(Doc::align_anchor("value"))
+    ///         .append(Doc::text(" = "))
+    ///         .append(Doc::text(value))
+    /// };
+    /// let doc = Doc::join(
+    ///     vec![field("id", "u32", "0"), field("name", "String", "x"), field("ok", "bool", "y")],
+    ///     Doc::hard_line(),
+    /// )
+    /// .align_scope();
+    /// assert_eq!(r#"
+    /// id:   u32    = 0
+    /// name: String = x
+    /// ok:   bool   = y"#.trim(), &print(&doc, &Default::default()));
+    ///
+    /// // Only the first anchor of each key on a line is aligned.
+    /// let doc = Doc::text("a")
+    ///     .append(Doc::align_anchor("type"))
+    ///     .append(Doc::text(": u8, bb"))
+    ///     .append(Doc::align_anchor("type"))
+    ///     .append(Doc::text(": u16"))
+    ///     .append(Doc::hard_line())
+    ///     .append(Doc::text("ccc"))
+    ///     .append(Doc::align_anchor("type"))
+    ///     .append(Doc::text(": u32"))
+    ///     .align_scope();
+    /// assert_eq!("a  : u8, bb: u16\nccc: u32", &print(&doc, &Default::default()));
+    ///
+    /// // Anchors of a group put on a single line don't pad each other.
+    /// let doc = Doc::join(
+    ///     vec![field("id", "u32", "0"), field("name", "String", "x")],
+    ///     Doc::text(",").append(Doc::line_or_space()),
+    /// )
+    /// .group()
+    /// .align_scope();
+    /// assert_eq!("id: u32 = 0, name: String = x", &print(&doc, &Default::default()));
+    ///
+    /// // Docs after the scope are also considered when checking if groups fit.
+    /// let doc = Doc::text("aaaa")
+    ///     .append(Doc::line_or_space())
+    ///     .append(Doc::text("bbbb"))
+    ///     .group()
+    ///     .align_scope()
+    ///     .append(Doc::text("cccc"));
+    /// assert_eq!("aaaa\nbbbbcccc", &print(&doc, &PrintOptions {
+    ///     width: 10,
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Printing panics if columns of anchors can't be settled,
+    /// for example, anchors with two keys appear in opposite orders on different lines.
+    pub fn align_scope(self) -> Doc<'a> {
+        Doc::AlignScope(Rc::new(self))
+    }
+
+    /// Simplify the doc tree without changing the printed result at any width.
+    ///
+    /// This flattens nested lists, drops nils, merges adjacent texts,
+    /// collapses and merges nests, and removes redundant groups.
+    /// Nests around texts and empty lines are kept, since they affect
+    /// [`trim_blank_lines`](crate::PrintOptions::trim_blank_lines).
+    /// Docs built by [`append`](Doc::append) and [`concat`](Doc::concat) are usually
+    /// full of these, so simplifying can make printing faster.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let doc = Doc::nil()
+    ///     .append(Doc::text("a"))
+    ///     .append(Doc::list(vec![Doc::text("b"), Doc::nil()]))
+    ///     .append(Doc::text("c").group())
+    ///     .nest(0);
+    /// let simplified = doc.clone().simplify();
+    /// assert!(matches!(&simplified, Doc::Text(text) if text == "abc"));
+    ///
+    /// let doc = Doc::text("a")
+    ///     .append(Doc::line_or_space().nest(2).group().nest(2))
+    ///     .append(Doc::text("b"))
+    ///     .group();
+    /// let simplified = doc.clone().simplify();
+    /// for width in [1, 80] {
+    ///     let options = PrintOptions { width, ..Default::default() };
+    ///     assert_eq!(print(&doc, &options), print(&simplified, &options));
+    /// }
+    ///
+    /// // Nests that affect trimming blank lines are kept.
+    /// let options = PrintOptions {
+    ///     width: 0,
+    ///     trim_blank_lines: true,
+    ///     ..Default::default()
+    /// };
+    /// let doc = Doc::empty_line().nest(2);
+    /// assert_eq!(print(&doc, &options), print(&doc.clone().simplify(), &options));
+    /// let doc = Doc::text("abcd")
+    ///     .append(
+    ///         Doc::line_or_nil()
+    ///             .append(Doc::text("a"))
+    ///             .group()
+    ///             .union(Doc::empty_line().nest(3)),
+    ///     )
+    ///     .group();
+    /// assert_eq!(print(&doc, &options), print(&doc.clone().simplify(), &options));
+    /// ```
+    pub fn simplify(self) -> Doc<'a> {
+        // Results of shared docs are kept with the original docs,
+        // so the original docs are alive and their pointers won't be reused.
+        let mut shared: Map<*const Doc<'a>, (Rc<Doc<'a>>, Rc<Doc<'a>>)> = Map::new();
+        let mut tasks = vec![Task::Simplify(self)];
+        let mut results: Vec<Simplified> = vec![];
+        while let Some(task) = tasks.pop() {
+            // Children are pushed in reverse order, so their results will be in order.
+            match task {
+                Task::Simplify(Doc::Alt(doc_flat, doc_break)) => tasks.extend([
+                    Task::Alt,
+                    Task::SimplifyRc(doc_break),
+                    Task::SimplifyRc(doc_flat),
+                ]),
+                Task::Simplify(Doc::Union(attempt, alternate)) => tasks.extend([
+                    Task::Union,
+                    Task::SimplifyRc(alternate),
+                    Task::SimplifyRc(attempt),
+                ]),
+                Task::Simplify(Doc::Nest(offset, doc)) => {
+                    tasks.extend([Task::Nest(offset), Task::SimplifyRc(doc)]);
+                }
+                Task::Simplify(Doc::NestAlign(offset, doc)) => {
+                    tasks.extend([Task::NestAlign(offset), Task::SimplifyRc(doc)]);
+                }
+                Task::Simplify(Doc::Group(docs)) => {
+                    tasks.push(Task::Group(docs.len()));
+                    tasks.extend(docs.into_iter().rev().map(Task::Simplify));
+                }
+                Task::Simplify(Doc::List(docs)) => {
+                    tasks.push(Task::List(docs.len()));
+                    tasks.extend(docs.into_iter().rev().map(Task::Simplify));
+                }
+                Task::Simplify(Doc::AlignScope(doc)) => {
+                    tasks.extend([Task::AlignScope, Task::SimplifyRc(doc)]);
+                }
+                Task::Simplify(doc) => results.push(Simplified::Owned(doc)),
+                Task::SimplifyRc(doc) => match Rc::try_unwrap(doc) {
+                    Ok(doc) => tasks.push(Task::Simplify(doc)),
+                    Err(doc) => {
+                        if let Some((_, simplified)) = shared.get(&Rc::as_ptr(&doc)) {
+                            results.push(Simplified::Shared(simplified.clone()));
+                        } else {
+                            let simplify = Task::Simplify((*doc).clone());
+                            tasks.extend([Task::Share(doc), simplify]);
+                        }
+                    }
+                },
+                Task::Share(doc) => {
+                    let simplified = pop_result(&mut results).into_rc();
+                    shared.insert(Rc::as_ptr(&doc), (doc, simplified.clone()));
+                    results.push(Simplified::Shared(simplified));
+                }
+                Task::Alt => {
+                    let doc_break = pop_result(&mut results).into_rc();
+                    let doc_flat = pop_result(&mut results).into_rc();
+                    results.push(Simplified::Owned(Doc::Alt(doc_flat, doc_break)));
+                }
+                Task::Union => {
+                    let alternate = pop_result(&mut results).into_rc();
+                    let attempt = pop_result(&mut results).into_rc();
+                    results.push(Simplified::Owned(Doc::Union(attempt, alternate)));
+                }
+                Task::Nest(offset) => {
+                    let doc = pop_result(&mut results);
+                    results.push(simplify_nest(offset, doc, false));
+                }
+                Task::NestAlign(offset) => {
+                    let doc = pop_result(&mut results);
+                    results.push(simplify_nest(offset, doc, true));
+                }
+                Task::Group(len) => {
+                    let mut docs = merge_docs(results.drain(results.len() - len..));
+                    let doc = if docs.len() == 1 && matches!(docs[0], Doc::Group(..)) {
+                        docs.remove(0)
+                    } else if docs.iter().any(is_mode_sensitive) {
+                        Doc::Group(docs)
+                    } else {
+                        // Group mode doesn't affect any docs inside, so it can be removed.
+                        unwrap_docs(docs)
+                    };
+                    results.push(Simplified::Owned(doc));
+                }
+                Task::List(len) => {
+                    let docs = merge_docs(results.drain(results.len() - len..));
+                    results.push(Simplified::Owned(unwrap_docs(docs)));
+                }
+                Task::AlignScope => {
+                    let doc = pop_result(&mut results).into_rc();
+                    results.push(Simplified::Owned(Doc::AlignScope(doc)));
+                }
+            }
+        }
+        let result = pop_result(&mut results);
+        drop(shared);
+        result.into_doc()
+    }
+
+    #[doc(hidden)]
+    /// Replace each `{}` in the format string with docs. Used by the [`doc!`](crate::doc!) macro.
+    pub fn __interpolate(fmt: &'a str, docs: Vec<Doc<'a>>) -> Doc<'a> {
+        let mut docs = docs.into_iter();
+        let mut parts = vec![];
+        let mut text = Cow::Borrowed("");
+        let mut rest = fmt;
+        while let Some(index) = rest.find(['{', '}']) {
+            let (escaped, next) = match &rest[index..] {
+                s if s.starts_with("{{") => ("{", &rest[index + 2..]),
+                s if s.starts_with("}}") => ("}", &rest[index + 2..]),
+                s if s.starts_with("{}") => {
+                    text += &rest[..index];
+                    if !text.is_empty() {
+                        parts.push(Doc::Text(core::mem::take(&mut text)));
+                    }
+                    parts.push(
+                        docs.next()
+                            .expect("missing argument for `{}` in format string"),
+                    );
+                    rest = &rest[index + 2..];
+                    continue;
+                }
+                _ => panic!("unmatched brace in format string"),
+            };
+            text += &rest[..index];
+            text.to_mut().push_str(escaped);
+            rest = next;
+        }
+        text += rest;
+        if !text.is_empty() {
+            parts.push(Doc::Text(text));
+        }
+        assert!(
+            docs.next().is_none(),
+            "too many arguments for format string"
+        );
+        Doc::List(parts)
+    }
+}
+
+/// Docs are simplified with an explicit stack instead of recursion,
+/// since they can be deeply nested.
+enum Task<'a> {
+    /// Simplify an owned doc.
+    Simplify(Doc<'a>),
+    /// Simplify a doc which may be shared by multiple parents.
+    SimplifyRc(Rc<Doc<'a>>),
+    /// Remember the result of a shared doc, so it will be reused.
+    Share(Rc<Doc<'a>>),
+    // Variants below build docs from results of children.
+    Alt,
+    Union,
+    Nest(usize),
+    NestAlign(usize),
+    Group(usize),
+    List(usize),
+    AlignScope,
+}
+
+/// Result of simplifying, which keeps shared docs shared when possible.
+enum Simplified<'a> {
+    Owned(Doc<'a>),
+    Shared(Rc<Doc<'a>>),
+}
+
+impl<'a> Simplified<'a> {
+    fn as_doc(&self) -> &Doc<'a> {
+        match self {
+            Simplified::Owned(doc) => doc,
+            Simplified::Shared(doc) => doc,
+        }
+    }
+
+    fn into_doc(self) -> Doc<'a> {
+        match self {
+            Simplified::Owned(doc) => doc,
+            Simplified::Shared(doc) => Rc::unwrap_or_clone(doc),
+        }
+    }
+
+    fn into_rc(self) -> Rc<Doc<'a>> {
+        match self {
+            Simplified::Owned(doc) => Rc::new(doc),
+            Simplified::Shared(doc) => doc,
+        }
+    }
+}
+
+fn pop_result<'a>(results: &mut Vec<Simplified<'a>>) -> Simplified<'a> {
+    results.pop().expect("missing result of simplified doc")
+}
+
+fn simplify_nest(offset: usize, doc: Simplified, align: bool) -> Simplified {
+    let nest = |offset, doc| {
+        if align {
+            Doc::NestAlign(offset, doc)
+        } else {
+            Doc::Nest(offset, doc)
+        }
+    };
+    let merged = match (align, doc.as_doc()) {
+        _ if offset == 0 => return doc,
+        // Nesting texts and empty lines can't be removed,
+        // because depth of nests affects trimming blank lines.
+        (_, Doc::Nil) => return doc,
+        (false, Doc::Nest(inner, doc)) | (true, Doc::NestAlign(inner, doc)) => offset
+            .checked_add(*inner)
+            .map(|offset| nest(offset, doc.clone())),
+        (false, Doc::Break(spaces, inner)) => offset
+            .checked_add(*inner)
+            .map(|offset| Doc::Break(*spaces, offset)),
+        _ => None,
+    };
+    Simplified::Owned(merged.unwrap_or_else(|| nest(offset, doc.into_rc())))
+}
+
+/// Flatten simplified docs of list or group into a single list.
+fn merge_docs<'a>(docs: impl Iterator<Item = Simplified<'a>>) -> Vec<Doc<'a>> {
+    let mut merged: Vec<Doc> = vec![];
+    let mut push = |doc| match (merged.last_mut(), doc) {
+        (_, Doc::Nil) => {}
+        (Some(Doc::Text(last)), Doc::Text(text)) => last.to_mut().push_str(&text),
+        (_, doc) => merged.push(doc),
+    };
+    for doc in docs {
+        match doc.into_doc() {
+            Doc::List(docs) => docs.into_iter().for_each(&mut push),
+            doc => push(doc),
+        }
+    }
+    merged
+}
+
+fn unwrap_docs(mut docs: Vec<Doc>) -> Doc {
+    match docs.len() {
+        0 => Doc::Nil,
+        1 => docs.remove(0),
+        _ => Doc::List(docs),
+    }
+}
+
+/// Check if printing result of a doc may be affected by the mode of its group.
+fn is_mode_sensitive(doc: &Doc) -> bool {
+    let mut docs = vec![doc];
+    while let Some(doc) = docs.pop() {
+        match doc {
+            Doc::Nil | Doc::Text(..) | Doc::NewLine | Doc::EmptyLine | Doc::AlignAnchor(..) => {}
+            Doc::Alt(..) | Doc::Union(..) | Doc::Break(..) | Doc::Group(..) => return true,
+            Doc::Nest(_, doc) | Doc::NestAlign(_, doc) | Doc::AlignScope(doc) => docs.push(doc),
+            Doc::List(children) => docs.extend(children),
+        }
+    }
+    false
+}
+
+impl<'a> From<&'a str> for Doc<'a> {
+    fn from(s: &'a str) -> Self {
+        Doc::text(s)
+    }
+}
+
+impl From<String> for Doc<'_> {
+    fn from(s: String) -> Self {
+        Doc::text(s)
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Doc<'a> {
+    fn from(s: Cow<'a, str>) -> Self {
+        Doc::text(s)
+    }
+}
+
+impl<'a> FromIterator<Doc<'a>> for Doc<'a> {
+    /// Collect docs into a list.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let doc: Doc = ["a", "b"].into_iter().map(Doc::text).collect();
+    /// assert_eq!("ab", &print(&doc, &Default::default()));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = Doc<'a>>>(iter: I) -> Self {
+        Doc::list(iter.into_iter().collect())
+    }
+}
+
+impl<'a> Extend<Doc<'a>> for Doc<'a> {
+    /// Append docs to the end, like [`concat`](Doc::concat).
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let mut doc = Doc::text("a");
+    /// doc.extend([Doc::text("b"), Doc::text("c")]);
+    /// assert_eq!("abc", &print(&doc, &Default::default()));
+    /// ```
+    fn extend<I: IntoIterator<Item = Doc<'a>>>(&mut self, iter: I) {
+        match self {
+            Doc::List(docs) => docs.extend(iter),
+            doc => *doc = core::mem::replace(doc, Doc::Nil).concat(iter.into_iter()),
+        }
+    }
+}
+
+impl<'a> Add for Doc<'a> {
+    type Output = Doc<'a>;
+
+    /// Join two docs, same as [`append`](Doc::append).
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let doc = Doc::text("a") + Doc::text("b");
+    /// assert_eq!("ab", &print(&doc, &Default::default()));
+    /// ```
+    fn add(self, rhs: Doc<'a>) -> Self::Output {
+        self.append(rhs)
+    }
+}
+
+impl<'a> AddAssign for Doc<'a> {
+    /// Append another doc, same as [`append`](Doc::append).
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let mut doc = Doc::text("a");
+    /// doc += Doc::text("b");
+    /// assert_eq!("ab", &print(&doc, &Default::default()));
+    /// ```
+    fn add_assign(&mut self, rhs: Doc<'a>) {
+        *self = core::mem::replace(self, Doc::Nil).append(rhs);
+    }
+}
+
+impl View for Doc<'_> {
+    fn view(&self) -> Node<'_, Self> {
+        match self {
+            Doc::Nil => Node::Nil,
+            Doc::Alt(doc_flat, doc_break) => Node::Alt(doc_flat, doc_break),
+            Doc::Union(attempt, alternate) => Node::Union(attempt, alternate),
+            Doc::Nest(offset, doc) => Node::Nest(*offset, doc),
+            Doc::NestAlign(offset, doc) => Node::NestAlign(*offset, doc),
+            Doc::Text(text) => Node::Text(text),
+            Doc::NewLine => Node::NewLine,
+            Doc::EmptyLine => Node::EmptyLine,
+            Doc::Break(spaces, offset) => Node::Break(*spaces, *offset),
+            Doc::Group(docs) => Node::Group(docs),
+            Doc::List(docs) => Node::List(docs),
+            Doc::AlignAnchor(key) => Node::AlignAnchor(key),
+            Doc::AlignScope(doc) => Node::AlignScope(doc),
+        }
+    }
 }
@@ -0,0 +1,240 @@
+use crate::Doc;
+#[cfg(not(feature = "sync"))]
+use alloc::rc::Rc;
+#[cfg(feature = "sync")]
+use alloc::sync::Arc as Rc;
+use alloc::{borrow::Cow, boxed::Box, collections::BTreeMap, vec, vec::Vec};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serialized representation of doc. See crate-level documentation for details.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Repr<'a> {
+    Nil,
+    Text {
+        text: Cow<'a, str>,
+    },
+    NewLine,
+    EmptyLine,
+    Break {
+        spaces: usize,
+        offset: usize,
+    },
+    FlatOrBreak {
+        flat: Box<Repr<'a>>,
+        #[serde(rename = "break")]
+        break_: Box<Repr<'a>>,
+    },
+    Union {
+        attempt: Box<Repr<'a>>,
+        alternate: Box<Repr<'a>>,
+    },
+    Nest {
+        offset: usize,
+        doc: Box<Repr<'a>>,
+    },
+    NestAlign {
+        offset: usize,
+        doc: Box<Repr<'a>>,
+    },
+    Group {
+        docs: Vec<Repr<'a>>,
+    },
+    List {
+        docs: Vec<Repr<'a>>,
+    },
+    AlignAnchor {
+        key: Cow<'a, str>,
+    },
+    AlignScope {
+        doc: Box<Repr<'a>>,
+    },
+    Shared {
+        id: usize,
+        doc: Box<Repr<'a>>,
+    },
+    Ref {
+        id: usize,
+    },
+}
+
+impl Serialize for Doc<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut sharing = Sharing {
+            counts: count_rcs(self),
+            ids: BTreeMap::new(),
+        };
+        to_repr(self, &mut sharing).serialize(serializer)
+    }
+}
+
+impl<'de, 'a> Deserialize<'de> for Doc<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut shared = BTreeMap::new();
+        from_repr(Repr::deserialize(deserializer)?, &mut shared).map_err(de::Error::custom)
+    }
+}
+
+struct Sharing<'a> {
+    /// How many times each doc behind `Rc` occurs in the doc tree being serialized.
+    counts: BTreeMap<*const Doc<'a>, usize>,
+    /// IDs of shared docs which have been serialized.
+    ids: BTreeMap<*const Doc<'a>, usize>,
+}
+
+/// Count occurrences of docs behind `Rc`s in the doc tree.
+///
+/// Strong count of `Rc` isn't used, since those `Rc`s may be held outside of the doc tree.
+/// Docs inside a shared doc are only counted once, because the shared doc is only serialized once.
+fn count_rcs<'a>(doc: &'a Doc) -> BTreeMap<*const Doc<'a>, usize> {
+    let mut counts = BTreeMap::new();
+    let mut docs = vec![doc];
+    let mut visit_rc = |doc: &'a Rc<Doc<'a>>, docs: &mut Vec<&'a Doc<'a>>| {
+        let count = counts.entry(Rc::as_ptr(doc)).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            docs.push(doc);
+        }
+    };
+    while let Some(doc) = docs.pop() {
+        match doc {
+            Doc::Nil
+            | Doc::Text(..)
+            | Doc::NewLine
+            | Doc::EmptyLine
+            | Doc::Break(..)
+            | Doc::AlignAnchor(..) => {}
+            Doc::Alt(a, b) | Doc::Union(a, b) => {
+                visit_rc(a, &mut docs);
+                visit_rc(b, &mut docs);
+            }
+            Doc::Nest(_, doc) | Doc::NestAlign(_, doc) | Doc::AlignScope(doc) => {
+                visit_rc(doc, &mut docs);
+            }
+            Doc::Group(children) | Doc::List(children) => docs.extend(children),
+        }
+    }
+    counts
+}
+
+fn to_repr<'a>(doc: &'a Doc, sharing: &mut Sharing<'a>) -> Repr<'a> {
+    match doc {
+        Doc::Nil => Repr::Nil,
+        Doc::Alt(doc_flat, doc_break) => Repr::FlatOrBreak {
+            flat: Box::new(rc_to_repr(doc_flat, sharing)),
+            break_: Box::new(rc_to_repr(doc_break, sharing)),
+        },
+        Doc::Union(attempt, alternate) => Repr::Union {
+            attempt: Box::new(rc_to_repr(attempt, sharing)),
+            alternate: Box::new(rc_to_repr(alternate, sharing)),
+        },
+        Doc::Nest(offset, doc) => Repr::Nest {
+            offset: *offset,
+            doc: Box::new(rc_to_repr(doc, sharing)),
+        },
+        Doc::NestAlign(offset, doc) => Repr::NestAlign {
+            offset: *offset,
+            doc: Box::new(rc_to_repr(doc, sharing)),
+        },
+        Doc::Text(text) => Repr::Text {
+            text: Cow::Borrowed(text),
+        },
+        Doc::NewLine => Repr::NewLine,
+        Doc::EmptyLine => Repr::EmptyLine,
+        Doc::Break(spaces, offset) => Repr::Break {
+            spaces: *spaces,
+            offset: *offset,
+        },
+        Doc::Group(docs) => Repr::Group {
+            docs: docs.iter().map(|doc| to_repr(doc, sharing)).collect(),
+        },
+        Doc::List(docs) => Repr::List {
+            docs: docs.iter().map(|doc| to_repr(doc, sharing)).collect(),
+        },
+        Doc::AlignAnchor(key) => Repr::AlignAnchor {
+            key: Cow::Borrowed(key),
+        },
+        Doc::AlignScope(doc) => Repr::AlignScope {
+            doc: Box::new(rc_to_repr(doc, sharing)),
+        },
+    }
+}
+
+/// Docs behind `Rc`s which occur more than once in the doc tree are only serialized once,
+/// then they're referenced by ID.
+fn rc_to_repr<'a>(doc: &'a Rc<Doc>, sharing: &mut Sharing<'a>) -> Repr<'a> {
+    let ptr = Rc::as_ptr(doc);
+    if sharing.counts[&ptr] == 1 {
+        return to_repr(doc, sharing);
+    }
+    if let Some(id) = sharing.ids.get(&ptr) {
+        Repr::Ref { id: *id }
+    } else {
+        let id = sharing.ids.len();
+        sharing.ids.insert(ptr, id);
+        Repr::Shared {
+            id,
+            doc: Box::new(to_repr(doc, sharing)),
+        }
+    }
+}
+
+fn from_repr<'a>(
+    repr: Repr,
+    shared: &mut BTreeMap<usize, Rc<Doc<'a>>>,
+) -> Result<Doc<'a>, UnknownId> {
+    Ok(match repr {
+        Repr::Nil => Doc::Nil,
+        Repr::Text { text } => Doc::Text(Cow::Owned(text.into_owned())),
+        Repr::NewLine => Doc::NewLine,
+        Repr::EmptyLine => Doc::EmptyLine,
+        Repr::Break { spaces, offset } => Doc::Break(spaces, offset),
+        Repr::FlatOrBreak { flat, break_ } => {
+            Doc::Alt(rc_from_repr(*flat, shared)?, rc_from_repr(*break_, shared)?)
+        }
+        Repr::Union { attempt, alternate } => Doc::Union(
+            rc_from_repr(*attempt, shared)?,
+            rc_from_repr(*alternate, shared)?,
+        ),
+        Repr::Nest { offset, doc } => Doc::Nest(offset, rc_from_repr(*doc, shared)?),
+        Repr::NestAlign { offset, doc } => Doc::NestAlign(offset, rc_from_repr(*doc, shared)?),
+        Repr::Group { docs } => Doc::Group(
+            docs.into_iter()
+                .map(|doc| from_repr(doc, shared))
+                .collect::<Result<_, _>>()?,
+        ),
+        Repr::List { docs } => Doc::List(
+            docs.into_iter()
+                .map(|doc| from_repr(doc, shared))
+                .collect::<Result<_, _>>()?,
+        ),
+        Repr::AlignAnchor { key } => Doc::AlignAnchor(Cow::Owned(key.into_owned())),
+        Repr::AlignScope { doc } => Doc::AlignScope(rc_from_repr(*doc, shared)?),
+        repr @ (Repr::Shared { .. } | Repr::Ref { .. }) => {
+            Rc::unwrap_or_clone(rc_from_repr(repr, shared)?)
+        }
+    })
+}
+
+fn rc_from_repr<'a>(
+    repr: Repr,
+    shared: &mut BTreeMap<usize, Rc<Doc<'a>>>,
+) -> Result<Rc<Doc<'a>>, UnknownId> {
+    match repr {
+        Repr::Shared { id, doc } => {
+            let doc = Rc::new(from_repr(*doc, shared)?);
+            shared.insert(id, doc.clone());
+            Ok(doc)
+        }
+        Repr::Ref { id } => shared.get(&id).cloned().ok_or(UnknownId(id)),
+        repr => from_repr(repr, shared).map(Rc::new),
+    }
+}
+
+struct UnknownId(usize);
+
+impl core::fmt::Display for UnknownId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "reference to unknown shared doc with id {}", self.0)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::Doc;
+use alloc::vec::Vec;
+
+impl<'a> Doc<'a> {
+    /// Join docs with the separator between each two of them.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc};
+    ///
+    /// let doc = Doc::join(
+    ///     vec![Doc::text("a"), Doc::text("b"), Doc::text("c")],
+    ///     Doc::text(", "),
+    /// );
+    /// assert_eq!("a, b, c", &print(&doc, &Default::default()));
+    /// ```
+    pub fn join(docs: impl IntoIterator<Item = Doc<'a>>, sep: Doc<'a>) -> Doc<'a> {
+        let mut joined = Vec::new();
+        for doc in docs {
+            if !joined.is_empty() {
+                joined.push(sep.clone());
+            }
+            joined.push(doc);
+        }
+        Doc::list(joined)
+    }
+}
+
+/// Extension methods for iterators whose items are docs.
+///
+/// ```
+/// use tiny_pretty::{print, Doc, DocIteratorExt};
+///
+/// let doc = ["a", "b", "c"]
+///     .into_iter()
+///     .map(Doc::text)
+///     .join(Doc::text(",").append(Doc::line_or_space()))
+///     .group();
+/// assert_eq!("a, b, c", &print(&doc, &Default::default()));
+/// ```
+pub trait DocIteratorExt<'a>: Iterator<Item = Doc<'a>> + Sized {
+    /// Join docs with the separator between each two of them.
+    /// See [`Doc::join`].
+    fn join(self, sep: Doc<'a>) -> Doc<'a> {
+        Doc::join(self, sep)
+    }
+
+    /// Join docs with the separator between each two of them,
+    /// then append `trailing` after the last doc if there're any docs.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, DocIteratorExt, PrintOptions};
+    ///
+    /// let doc = ["a", "b"]
+    ///     .into_iter()
+    ///     .map(Doc::text)
+    ///     .join_with_trailing(
+    ///         Doc::text(",").append(Doc::line_or_space()),
+    ///         Doc::flat_or_break(Doc::nil(), Doc::text(",")),
+    ///     )
+    ///     .group();
+    /// assert_eq!("a, b", &print(&doc, &Default::default()));
+    /// assert_eq!("a,\nb,", &print(&doc, &PrintOptions {
+    ///     width: 2,
+    ///     ..Default::default()
+    /// }));
+    ///
+    /// let doc = std::iter::empty().join_with_trailing(Doc::text(","), Doc::text(","));
+    /// assert!(print(&doc, &Default::default()).is_empty());
+    /// ```
+    fn join_with_trailing(self, sep: Doc<'a>, trailing: Doc<'a>) -> Doc<'a> {
+        let mut docs = self.peekable();
+        if docs.peek().is_none() {
+            return Doc::nil();
+        }
+        Doc::join(docs, sep).append(trailing)
+    }
+
+    /// Concatenate docs, then surround them with `open` and `close`.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, DocIteratorExt};
+    ///
+    /// let doc = ["a", "b"]
+    ///     .into_iter()
+    ///     .map(Doc::text)
+    ///     .surround(Doc::text("["), Doc::text("]"));
+    /// assert_eq!("[ab]", &print(&doc, &Default::default()));
+    /// ```
+    fn surround(self, open: Doc<'a>, close: Doc<'a>) -> Doc<'a> {
+        open.concat(self).append(close)
+    }
+}
+
+impl<'a, I: Iterator<Item = Doc<'a>>> DocIteratorExt<'a> for I {}
@@ -40,9 +40,9 @@
 //! If they're too long to fit, we insert line break with indentation:
 //!
 //! - When being on a single line, there're no spaces after left paren and before right paren,
-//! and there must be a space after each argument comma.
+//!   and there must be a space after each argument comma.
 //! - When being splitted into different lines, there must be indentation when printing arguments,
-//! and there must be a line break between arguments.
+//!   and there must be a line break between arguments.
 //!
 //! So, we can build [`Doc`] like this:
 //!
@@ -51,18 +51,18 @@
 //! #     name: String,
 //! #     args: Vec<FunctionCall>,
 //! # }
-//! use itertools::Itertools;
-//! use tiny_pretty::Doc;
+//! use tiny_pretty::{Doc, DocIteratorExt};
 //!
 //! fn build_doc(fn_call: &FunctionCall) -> Doc {
 //!     Doc::text(&fn_call.name)
 //!         .append(Doc::text("("))
 //!         .append(
 //!             Doc::line_or_nil()
-//!                 .concat(Itertools::intersperse(
-//!                     fn_call.args.iter().map(build_doc),
-//!                     Doc::text(",").append(Doc::line_or_space())
-//!                 ))
+//!                 .append(
+//!                     fn_call.args.iter()
+//!                         .map(build_doc)
+//!                         .join(Doc::text(",").append(Doc::line_or_space()))
+//!                 )
 //!                 .nest(2)
 //!                 .append(Doc::line_or_nil())
 //!                 .group()
@@ -78,8 +78,7 @@
 //! #     name: String,
 //! #     args: Vec<FunctionCall>,
 //! # }
-//! # use itertools::Itertools;
-//! # use tiny_pretty::Doc;
+//! # use tiny_pretty::{Doc, DocIteratorExt};
 //! # let fn_call = FunctionCall {
 //! #     name: "foo".into(),
 //! #     args: vec![
@@ -95,10 +94,11 @@
 //! #         .append(Doc::text("("))
 //! #         .append(
 //! #             Doc::line_or_nil()
-//! #                 .append(Doc::list(Itertools::intersperse(
-//! #                     fn_call.args.iter().map(build_doc),
-//! #                     Doc::text(",").append(Doc::line_or_space())
-//! #                 ).collect()))
+//! #                 .append(
+//! #                     fn_call.args.iter()
+//! #                         .map(build_doc)
+//! #                         .join(Doc::text(",").append(Doc::line_or_space()))
+//! #                 )
 //! #                 .nest(2)
 //! #                 .append(Doc::line_or_nil())
 //! #                 .group()
@@ -123,8 +123,7 @@
 //! #     name: String,
 //! #     args: Vec<FunctionCall>,
 //! # }
-//! # use itertools::Itertools;
-//! # use tiny_pretty::Doc;
+//! # use tiny_pretty::{Doc, DocIteratorExt};
 //! use tiny_pretty::{print, PrintOptions};
 //!
 //! let fn_call = FunctionCall {
@@ -142,10 +141,11 @@
 //! #         .append(Doc::text("("))
 //! #         .append(
 //! #             Doc::line_or_nil()
-//! #                 .append(Doc::list(Itertools::intersperse(
-//! #                     fn_call.args.iter().map(build_doc),
-//! #                     Doc::text(",").append(Doc::line_or_space())
-//! #                 ).collect()))
+//! #                 .append(
+//! #                     fn_call.args.iter()
+//! #                         .map(build_doc)
+//! #                         .join(Doc::text(",").append(Doc::line_or_space()))
+//! #                 )
 //! #                 .nest(2)
 //! #                 .append(Doc::line_or_nil())
 //! #                 .group()
@@ -162,6 +162,35 @@
 //! You can specify advanced printing options, such as controlling line break and
 //! indentation kind. See [`PrintOptions`] for details.
 //!
+//! ## Pretty Printing Values
+//!
+//! The [`Pretty`] trait converts values into docs, and it's implemented for standard types
+//! like integers, strings, tuples and collections.
+//! Enable the `derive` feature gate to derive it for your own structs and enums:
+//!
+//! ```
+//! # #[cfg(feature = "derive")]
+//! # {
+//! use tiny_pretty::Pretty;
+//!
+//! #[derive(Pretty)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! let points = vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+//! assert_eq!("[Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]", points.to_pretty_string(80));
+//! # }
+//! ```
+//!
+//! ## Streaming Output
+//!
+//! For very large output, building a giant doc before printing can use a lot of memory.
+//! [`StreamPrinter`] accepts top-level docs one at a time, and writes finished output to
+//! any [`core::fmt::Write`] sink as soon as possible.
+//! With the `std` feature gate, `IoWriter` adapts [`std::io::Write`] as the sink.
+//!
 //! ## Text Width Measurement
 //!
 //! By default, text width is measured as "visual width".
@@ -170,11 +199,154 @@
 //! But sometimes for some Unicode characters, you may want the column to
 //! be close to width limitation as possible, though it will exceed visually.
 //! To achieve that, please enable the `unicode-width` feature gate.
+//!
+//! ## Arena-allocated Docs
+//!
+//! Each [`Doc`] node may allocate an `Rc` or a `Vec`,
+//! which can be expensive when building millions of nodes for large files.
+//! Enabling the `arena` feature gate provides `ArenaDoc` and `DocArena`,
+//! which allocate docs in a few large chunks and are printed with the same algorithm.
+//!
+//! ## `no_std` Support
+//!
+//! This crate only requires `alloc`. Disable the default `std` feature gate
+//! to use it in `no_std` environments.
+//!
+//! ## Serialization
+//!
+//! Enabling the `serde` feature gate implements `Serialize` and `Deserialize` for [`Doc`],
+//! so docs can be snapshotted or sent between processes.
+//!
+//! Each doc is serialized as a map with a `type` field, and other fields depend on the type:
+//!
+//! | `type`          | Fields                                        | Created by                    |
+//! | --------------- | --------------------------------------------- | ----------------------------- |
+//! | `nil`           |                                               | [`Doc::nil`]                  |
+//! | `text`          | `text`: string                                | [`Doc::text`]                 |
+//! | `new_line`      |                                               | [`Doc::hard_line`]            |
+//! | `empty_line`    |                                               | [`Doc::empty_line`]           |
+//! | `break`         | `spaces`: integer, `offset`: integer          | [`Doc::line_or_space`], etc.  |
+//! | `flat_or_break` | `flat`: doc, `break`: doc                     | [`Doc::flat_or_break`]        |
+//! | `union`         | `attempt`: doc, `alternate`: doc              | [`Doc::union`]                |
+//! | `nest`          | `offset`: integer, `doc`: doc                 | [`Doc::nest`]                 |
+//! | `nest_align`    | `offset`: integer, `doc`: doc                 | [`Doc::nest_align`]           |
+//! | `group`         | `docs`: array of docs                         | [`Doc::group`]                |
+//! | `list`          | `docs`: array of docs                         | [`Doc::list`], [`Doc::append`]|
+//! | `align_anchor`  | `key`: string                                 | [`Doc::align_anchor`]         |
+//! | `align_scope`   | `doc`: doc                                    | [`Doc::align_scope`]          |
+//! | `shared`        | `id`: integer, `doc`: doc                     |                               |
+//! | `ref`           | `id`: integer                                 |                               |
+//!
+//! Docs inside [`flat_or_break`](Doc::flat_or_break), [`union`](Doc::union) and [`nest`](Doc::nest)
+//! are stored in `Rc`s. If such a doc occurs more than once in the serialized doc,
+//! the first occurrence will be serialized as `shared` with an unique ID,
+//! and later occurrences will be serialized as `ref` with that ID,
+//! so sharing is preserved after deserializing.
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use tiny_pretty::Doc;
+//!
+//! let doc = Doc::text("a").append(Doc::line_or_space().nest(2)).group();
+//! let json = serde_json::to_string(&doc).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"type":"group","docs":[{"type":"text","text":"a"},{"type":"break","spaces":1,"offset":2}]}"#,
+//! );
+//!
+//! let shared = Doc::text("x").nest(2);
+//! let doc = Doc::list(vec![shared.clone(), shared]);
+//! let json = serde_json::to_string(&doc).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"type":"list","docs":[{"type":"nest","offset":2,"doc":{"type":"shared","id":0,"doc":{"type":"text","text":"x"}}},{"type":"nest","offset":2,"doc":{"type":"ref","id":0}}]}"#,
+//! );
+//!
+//! let doc: Doc = serde_json::from_str(&json).unwrap();
+//! assert_eq!(serde_json::to_string(&doc).unwrap(), json);
+//!
+//! // `shared` is still alive, but it only occurs once in the doc.
+//! let shared = Doc::text("x").nest(2);
+//! let doc = Doc::list(vec![shared.clone()]);
+//! assert_eq!(
+//!     serde_json::to_string(&doc).unwrap(),
+//!     r#"{"type":"list","docs":[{"type":"nest","offset":2,"doc":{"type":"text","text":"x"}}]}"#,
+//! );
+//! # }
+//! ```
+//!
+//! ## Thread Safety
+//!
+//! By default, [`Doc`] uses `Rc` internally, so it can't be sent to other threads.
+//! Enabling the `sync` feature gate makes it use `Arc` instead,
+//! so docs can be built on a worker thread and printed elsewhere,
+//! and shared sub-docs can be cached across threads.
+//! All constructors and printing functions work the same in both modes.
+//!
+//! ```
+//! # #[cfg(feature = "sync")]
+//! # {
+//! use std::thread;
+//! use tiny_pretty::{print, Doc};
+//!
+//! let doc = thread::spawn(|| Doc::text("a").append(Doc::line_or_space()).append(Doc::text("b")).group())
+//!     .join()
+//!     .unwrap();
+//! assert_eq!("a b", &print(&doc, &Default::default()));
+//! # }
+//! ```
+
+#![no_std]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "arena")]
+mod arena;
+mod builder_code;
+mod debug;
 mod doc;
+#[cfg(feature = "serde")]
+mod doc_serde;
+mod join;
+mod macros;
 mod options;
+mod pretty;
 mod print;
+mod reflow;
+mod sequence;
+#[cfg(feature = "serde")]
+mod ser;
+mod stream;
+mod table;
+mod validate;
+mod view;
 
+#[cfg(feature = "arena")]
+pub use arena::{ArenaDoc, DocArena};
+pub use debug::{pretty_debug, PrettyDebug};
 pub use doc::Doc;
+pub use join::DocIteratorExt;
 pub use options::*;
-pub use print::print;
+pub use pretty::Pretty;
+pub use print::{print, print_with_overflows, print_with_stats, Overflow, PrintStats, Printable};
+pub use reflow::ReflowOptions;
+pub use sequence::{Sequence, TrailingSeparator};
+#[cfg(feature = "serde")]
+pub use ser::{to_doc, DocSerializer, SerializeError, SerializeStyle};
+#[cfg(feature = "std")]
+pub use stream::IoWriter;
+pub use stream::StreamPrinter;
+pub use table::{Alignment, Table};
+#[cfg(feature = "derive")]
+pub use tiny_pretty_derive::Pretty;
+pub use validate::{ValidationIssue, ValidationIssueKind};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::macros::{check_format, is_format};
+    pub use crate::pretty::{field, named_fields, unnamed_fields};
+    pub use alloc::{vec, vec::Vec};
+}
@@ -0,0 +1,247 @@
+#[macro_export]
+/// Build docs concisely.
+///
+/// It accepts comma-separated elements and joins them as a list.
+/// Each element can be:
+///
+/// - Any expression that can be converted into doc, such as string literals, strings and docs.
+/// - Keywords which are names of doc constructors without arguments:
+///   `nil`, `space`, `hard_line`, `soft_line`, `empty_line`, `line_or_space` and `line_or_nil`.
+/// - `group(...)`, `nest(size, ...)` and `nest_align(size, ...)`, which accept elements like
+///   this macro, then call [`group`](crate::Doc::group), [`nest`](crate::Doc::nest)
+///   or [`nest_align`](crate::Doc::nest_align) on them.
+/// - `flat_or_break(a, b)` and `union(a, b)`, which accept two elements.
+/// - `fmt("...", args...)`, which replaces each `{}` in the format string with docs of arguments.
+///   Use `{{` and `}}` to escape braces.
+///   Unmatched braces and wrong number of arguments are reported at compile time.
+/// - `[...]`, which is a nested list of elements.
+///
+/// Like [`format!`], a leading string literal followed by expressions is a format string,
+/// if the number of `{}` placeholders in it equals the number of expressions.
+/// Otherwise, or if any following element is a keyword or a nested form above,
+/// the leading string literal is plain text.
+///
+/// ```
+/// use tiny_pretty::{doc, print, Doc, PrintOptions};
+///
+/// let name = "main";
+/// let args = doc!["a,", line_or_space, "b"];
+/// let doc = doc!["fn ", name, "(", group(nest(2, line_or_nil, args), line_or_nil), ")"];
+///
+/// assert_eq!("fn main(a, b)", &print(&doc, &Default::default()));
+/// assert_eq!("fn main(\n  a,\n  b\n)", &print(&doc, &PrintOptions {
+///     width: 10,
+///     ..Default::default()
+/// }));
+///
+/// let lhs = Doc::text("x");
+/// let doc = doc![fmt("let {} = {};", lhs, "1")];
+/// assert_eq!("let x = 1;", &print(&doc, &Default::default()));
+///
+/// let (lhs, rhs) = (Doc::text("y"), Doc::text("2"));
+/// let doc = doc!("let {} = {};", lhs, rhs);
+/// assert_eq!("let y = 2;", &print(&doc, &Default::default()));
+///
+/// let doc = doc!["fn main() {}", hard_line];
+/// assert_eq!("fn main() {}\n", &print(&doc, &Default::default()));
+/// ```
+///
+/// Since keywords are matched by name, variables with the same names as keywords
+/// can't be used as elements directly; wrap them with parentheses instead.
+///
+/// Errors in format strings of `fmt(...)` are reported at compile time:
+///
+/// ```compile_fail
+/// use tiny_pretty::doc;
+///
+/// let doc = doc![fmt("let {} = {};", "x")];
+/// ```
+macro_rules! doc {
+    (@finish) => {
+        $crate::Doc::nil()
+    };
+    (@finish $doc:expr,) => {
+        $doc
+    };
+    (@finish $($docs:expr,)*) => {
+        $crate::Doc::list($crate::__private::vec![$($docs),*])
+    };
+
+    (@munch [$($done:expr,)*]) => {
+        $crate::doc!(@finish $($done,)*)
+    };
+    (@munch [$($done:expr,)*] nil $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::nil(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] space $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::space(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] hard_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::hard_line(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] soft_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::soft_line(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] empty_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::empty_line(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] line_or_space $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::line_or_space(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] line_or_nil $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::line_or_nil(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] group($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::doc!($($inner)*).group(),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] nest($size:expr, $($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::doc!($($inner)*).nest($size),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] nest_align($size:expr, $($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::doc!($($inner)*).nest_align($size),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] flat_or_break($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* {
+            let [doc_flat, doc_break] = $crate::doc!(@pair [] $($inner)*);
+            $crate::Doc::flat_or_break(doc_flat, doc_break)
+        },] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] union($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* {
+            let [attempt, alternate] = $crate::doc!(@pair [] $($inner)*);
+            attempt.union(alternate)
+        },] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] fmt($fmt:literal $(, $arg:expr)* $(,)?) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* {
+            const _: () = $crate::__private::check_format(
+                $fmt,
+                <[&str]>::len(&[$(stringify!($arg)),*]),
+            );
+            $crate::Doc::__interpolate(
+                $fmt,
+                $crate::__private::vec![$($crate::Doc::from($arg)),*],
+            )
+        },] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::doc!($($inner)*),] $($($rest)*)?)
+    };
+    (@munch [$($done:expr,)*] $doc:expr $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [$($done,)* $crate::Doc::from($doc),] $($($rest)*)?)
+    };
+
+    // Split two elements by munching the first one as a single-element list.
+    (@pair [$($first:tt)*] , $($second:tt)+) => {
+        [$crate::doc!($($first)*), $crate::doc!($($second)+)]
+    };
+    (@pair [$($first:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::doc!(@pair [$($first)* $next] $($rest)*)
+    };
+
+    // Collect expressions after the leading string literal,
+    // and give up if there're keywords or nested forms.
+    (@leading $text:literal [$($arg:expr,)*] [$($all:tt)*]) => {{
+        const FORMAT: bool = $crate::__private::is_format(
+            $text,
+            <[&str]>::len(&[$(stringify!($arg)),*]),
+        );
+        if FORMAT {
+            $crate::Doc::__interpolate(
+                $text,
+                $crate::__private::vec![$($crate::Doc::from($arg)),*],
+            )
+        } else {
+            $crate::doc!(@munch [] $text, $($all)*)
+        }
+    }};
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] nil $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] space $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] hard_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] soft_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] empty_line $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] line_or_space $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] line_or_nil $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] group($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] nest($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] nest_align($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] flat_or_break($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] union($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] fmt($($inner:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:tt)*] [$($all:tt)*] [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::doc!(@munch [] $text, $($all)*)
+    };
+    (@leading $text:literal [$($done:expr,)*] [$($all:tt)*] $arg:expr $(, $($rest:tt)*)?) => {
+        $crate::doc!(@leading $text [$($done,)* $arg,] [$($all)*] $($($rest)*)?)
+    };
+
+    ($text:literal, $($rest:tt)+) => {
+        $crate::doc!(@leading $text [] [$($rest)+] $($rest)+)
+    };
+    ($($elements:tt)*) => {
+        $crate::doc!(@munch [] $($elements)*)
+    };
+}
+
+#[doc(hidden)]
+/// Check format string of `fmt(...)` in `doc!` at compile time.
+pub const fn check_format(fmt: &str, args: usize) {
+    match count_placeholders(fmt) {
+        None => panic!("unmatched brace in format string"),
+        Some(count) if count > args => panic!("missing argument for `{{}}` in format string"),
+        Some(count) if count < args => panic!("too many arguments for format string"),
+        Some(_) => {}
+    }
+}
+
+#[doc(hidden)]
+/// Check if the leading string literal of `doc!` is a format string for following arguments.
+pub const fn is_format(text: &str, args: usize) -> bool {
+    matches!(count_placeholders(text), Some(count) if count == args)
+}
+
+/// Count `{}` placeholders in format string, or return `None` if there're unmatched braces.
+const fn count_placeholders(fmt: &str) -> Option<usize> {
+    let bytes = fmt.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let next = if i + 1 < bytes.len() { bytes[i + 1] } else { 0 };
+        match (bytes[i], next) {
+            (b'{', b'{') | (b'}', b'}') => i += 2,
+            (b'{', b'}') => {
+                count += 1;
+                i += 2;
+            }
+            (b'{' | b'}', _) => return None,
+            _ => i += 1,
+        }
+    }
+    Some(count)
+}
@@ -45,6 +45,8 @@ pub struct PrintOptions {
     /// Specifically, it prints `indent / tab_size` times tabs
     /// then prints `indent % tab_size` times spaces.
     /// See the documentation and examples of the [`tab_size`](PrintOptions::tab_size) option below.
+    /// For alignment which should always be printed as spaces,
+    /// use [`nest_align`](crate::Doc::nest_align) instead.
     ///
     /// Default value is space.
     ///
@@ -68,6 +70,7 @@ pub struct PrintOptions {
     /// The limitation that pretty printer should *(but not must)* avoid columns exceeding.
     /// Pretty printer will try its best to keep column width less than this value,
     /// but it may exceed for some cases, for example, a very very long single word.
+    /// Use [`print_with_overflows`](crate::print_with_overflows) to find out those lines.
     ///
     /// Default value is 80.
     ///
@@ -131,6 +134,140 @@ pub struct PrintOptions {
     /// }));
     /// ```
     pub tab_size: usize,
+
+    /// Maximum number of docs after a group to visit
+    /// when checking if the group can be put on a single line.
+    ///
+    /// Without limitation, the printer will check the docs after the group until there's
+    /// a line break, so it may scan far past the current group on long flat runs.
+    /// With limitation, the cost of each group is predictable,
+    /// but the trade-off is that the group may be put on a single line
+    /// even if the docs after it make the line exceed width limitation.
+    ///
+    /// Default value is `None`, which means no limitation.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let doc = Doc::list(vec![Doc::text("aaaa"), Doc::line_or_space(), Doc::text("bbbb")])
+    ///     .group()
+    ///     .append(Doc::text("cccc"));
+    ///
+    /// assert_eq!("aaaa\nbbbbcccc", &print(&doc, &PrintOptions {
+    ///     width: 10,
+    ///     ..Default::default()
+    /// }));
+    ///
+    /// assert_eq!("aaaa bbbbcccc", &print(&doc, &PrintOptions {
+    ///     width: 10,
+    ///     lookahead: Some(0),
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub lookahead: Option<usize>,
+
+    /// Maximum number of consecutive blank lines created by [`empty_line`](crate::Doc::empty_line).
+    /// Extra blank lines will be dropped.
+    ///
+    /// Default value is `None`, which means no limitation.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let doc = Doc::list(vec![
+    ///     Doc::text("a"),
+    ///     Doc::empty_line(),
+    ///     Doc::empty_line(),
+    ///     Doc::empty_line(),
+    ///     Doc::hard_line(),
+    ///     Doc::text("b"),
+    /// ]);
+    ///
+    /// assert_eq!("a\n\n\n\nb", &print(&doc, &Default::default()));
+    ///
+    /// assert_eq!("a\n\nb", &print(&doc, &PrintOptions {
+    ///     max_blank_lines: Some(1),
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub max_blank_lines: Option<usize>,
+
+    /// Drop blank lines created by [`empty_line`](crate::Doc::empty_line)
+    /// at the start and the end of nested blocks.
+    ///
+    /// Blocks are recognized by [nests](crate::Doc::nest): blank lines are dropped
+    /// when they're created right after entering a nest, or right before leaving a nest.
+    /// Blank lines after a nest has been left, such as after continuation lines, are kept.
+    ///
+    /// Default value is `false`.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let doc = Doc::list(vec![
+    ///     Doc::text("{"),
+    ///     Doc::list(vec![
+    ///         Doc::empty_line(),
+    ///         Doc::hard_line(),
+    ///         Doc::text("a"),
+    ///         Doc::empty_line(),
+    ///         Doc::hard_line(),
+    ///         Doc::text("b"),
+    ///         Doc::empty_line(),
+    ///     ])
+    ///     .nest(2),
+    ///     Doc::hard_line(),
+    ///     Doc::text("}"),
+    /// ]);
+    ///
+    /// assert_eq!("{\n\n  a\n\n  b\n\n}", &print(&doc, &Default::default()));
+    ///
+    /// assert_eq!("{\n  a\n\n  b\n}", &print(&doc, &PrintOptions {
+    ///     trim_blank_lines: true,
+    ///     ..Default::default()
+    /// }));
+    ///
+    /// let doc = Doc::list(vec![
+    ///     Doc::text("let x = foo"),
+    ///     Doc::hard_line().append(Doc::text(".bar();")).nest(4),
+    ///     Doc::empty_line(),
+    ///     Doc::hard_line(),
+    ///     Doc::text("let y = 1;"),
+    /// ]);
+    /// assert_eq!("let x = foo\n    .bar();\n\nlet y = 1;", &print(&doc, &PrintOptions {
+    ///     trim_blank_lines: true,
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub trim_blank_lines: bool,
+
+    /// Ensure the output ends with exactly one line break,
+    /// unless the output is empty.
+    ///
+    /// Default value is `false`.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, LineBreak, PrintOptions};
+    ///
+    /// let options = PrintOptions {
+    ///     final_newline: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let doc = Doc::text("a");
+    /// assert_eq!("a\n", &print(&doc, &options));
+    ///
+    /// let doc = Doc::list(vec![Doc::text("a"), Doc::empty_line(), Doc::hard_line().nest(2)]);
+    /// assert_eq!("a\n", &print(&doc, &options));
+    ///
+    /// assert_eq!("a\r\n", &print(&Doc::text("a"), &PrintOptions {
+    ///     line_break: LineBreak::Crlf,
+    ///     ..options.clone()
+    /// }));
+    ///
+    /// assert!(print(&Doc::nil(), &options).is_empty());
+    /// ```
+    pub final_newline: bool,
 }
 
 impl Default for PrintOptions {
@@ -140,6 +277,10 @@ impl Default for PrintOptions {
             indent_kind: Default::default(),
             width: 80,
             tab_size: 2,
+            lookahead: None,
+            max_blank_lines: None,
+            trim_blank_lines: false,
+            final_newline: false,
         }
     }
 }
@@ -0,0 +1,317 @@
+use crate::{print, Doc, PrintOptions, Sequence, TrailingSeparator};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+/// Indentation size of nested items when a value is broken into different lines.
+pub(crate) const INDENT: usize = 4;
+
+/// Types that can be converted into doc for pretty printing.
+///
+/// Implementations for standard types lay out values like Rust's `Debug` does:
+/// they're put on a single line if possible; otherwise each item is placed on its own line
+/// with indentation and trailing comma.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use tiny_pretty::Pretty;
+///
+/// let value = vec![(1, "one"), (2, "two")];
+/// assert_eq!(r#"[(1, "one"), (2, "two")]"#, value.to_pretty_string(80));
+/// assert_eq!(r#"
+/// [
+///     (1, "one"),
+///     (2, "two"),
+/// ]"#.trim(), value.to_pretty_string(15));
+///
+/// let value = BTreeMap::from([("key", Some(vec![1, 2, 3]))]);
+/// assert_eq!(r#"{"key": Some([1, 2, 3])}"#, value.to_pretty_string(80));
+/// assert_eq!(r#"
+/// {
+///     "key": Some(
+///         [1, 2, 3],
+///     ),
+/// }"#.trim(), value.to_pretty_string(20));
+/// ```
+pub trait Pretty {
+    /// Build doc for this value.
+    fn pretty(&self) -> Doc<'_>;
+
+    #[doc(hidden)]
+    /// Docs of fields or entries that will be inlined into the parent
+    /// when marked as `#[pretty(flatten)]` by `#[derive(Pretty)]`.
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        None
+    }
+
+    /// Pretty print this value with the given width limitation and default options.
+    fn to_pretty_string(&self, width: usize) -> String {
+        print(
+            &self.pretty(),
+            &PrintOptions {
+                width,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// Build a comma-separated sequence surrounded by delimiters.
+///
+/// It's put on a single line if possible;
+/// otherwise each item is placed on its own line with trailing comma.
+pub(crate) fn sequence<'a>(
+    open: &'a str,
+    docs: impl IntoIterator<Item = Doc<'a>>,
+    close: &'a str,
+) -> Doc<'a> {
+    Sequence::new(Doc::text(open), Doc::text(close))
+        .trailing(TrailingSeparator::IfBroken)
+        .indent(INDENT)
+        .build(docs)
+}
+
+/// Same as [`sequence`], but there're spaces around items when they're on a single line.
+pub(crate) fn braced<'a>(
+    open: &'a str,
+    docs: impl IntoIterator<Item = Doc<'a>>,
+    close: &'a str,
+) -> Doc<'a> {
+    Sequence::new(Doc::text(open), Doc::text(close))
+        .trailing(TrailingSeparator::IfBroken)
+        .padding(true)
+        .indent(INDENT)
+        .build(docs)
+}
+
+/// Build doc of single-element tuple, which requires trailing comma even on a single line.
+pub(crate) fn single_tuple(doc: Doc) -> Doc {
+    Sequence::new(Doc::text("("), Doc::text(")"))
+        .trailing(TrailingSeparator::Always)
+        .indent(INDENT)
+        .build([doc])
+}
+
+#[doc(hidden)]
+/// Build doc of struct or enum variant with named fields. Used by `#[derive(Pretty)]`.
+pub fn named_fields<'a>(name: &'a str, fields: Vec<Doc<'a>>) -> Doc<'a> {
+    Doc::text(name)
+        .append(Doc::space())
+        .append(braced("{", fields, "}"))
+}
+
+#[doc(hidden)]
+/// Build doc of struct or enum variant with unnamed fields. Used by `#[derive(Pretty)]`.
+pub fn unnamed_fields<'a>(name: &'a str, fields: Vec<Doc<'a>>) -> Doc<'a> {
+    Doc::text(name).append(sequence("(", fields, ")"))
+}
+
+#[doc(hidden)]
+/// Build doc of a named field. Used by `#[derive(Pretty)]`.
+pub fn field<'a>(name: &'a str, value: Doc<'a>) -> Doc<'a> {
+    Doc::text(name).append(Doc::text(": ")).append(value)
+}
+
+fn entry<'a>(key: &'a impl Pretty, value: &'a impl Pretty) -> Doc<'a> {
+    key.pretty().append(Doc::text(": ")).append(value.pretty())
+}
+
+macro_rules! impl_pretty_display {
+    ($($t:ty),*) => {
+        $(
+            impl Pretty for $t {
+                fn pretty(&self) -> Doc<'_> {
+                    Doc::text(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_pretty_display!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool);
+
+macro_rules! impl_pretty_debug {
+    ($($t:ty),*) => {
+        $(
+            impl Pretty for $t {
+                fn pretty(&self) -> Doc<'_> {
+                    Doc::text(format!("{self:?}"))
+                }
+            }
+        )*
+    };
+}
+
+// `Debug` output of these types is quoted and escaped, and floats always have decimal point.
+impl_pretty_debug!(f32, f64, char, str, String);
+
+impl Pretty for Cow<'_, str> {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+}
+
+impl Pretty for () {
+    fn pretty(&self) -> Doc<'_> {
+        Doc::text("()")
+    }
+}
+
+impl<T: Pretty + ?Sized> Pretty for &T {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        (**self).__pretty_fields()
+    }
+}
+
+impl<T: Pretty + ?Sized> Pretty for &mut T {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        (**self).__pretty_fields()
+    }
+}
+
+impl<T: Pretty + ?Sized> Pretty for Box<T> {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        (**self).__pretty_fields()
+    }
+}
+
+impl<T: Pretty + ?Sized> Pretty for Rc<T> {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        (**self).__pretty_fields()
+    }
+}
+
+impl<T: Pretty + ?Sized> Pretty for Arc<T> {
+    fn pretty(&self) -> Doc<'_> {
+        (**self).pretty()
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        (**self).__pretty_fields()
+    }
+}
+
+impl<T: Pretty> Pretty for Option<T> {
+    fn pretty(&self) -> Doc<'_> {
+        match self {
+            Some(value) => sequence("Some(", [value.pretty()], ")"),
+            None => Doc::text("None"),
+        }
+    }
+}
+
+impl<T: Pretty, E: Pretty> Pretty for Result<T, E> {
+    fn pretty(&self) -> Doc<'_> {
+        match self {
+            Ok(value) => sequence("Ok(", [value.pretty()], ")"),
+            Err(error) => sequence("Err(", [error.pretty()], ")"),
+        }
+    }
+}
+
+impl<T: Pretty> Pretty for [T] {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("[", self.iter().map(Pretty::pretty), "]")
+    }
+}
+
+impl<T: Pretty, const N: usize> Pretty for [T; N] {
+    fn pretty(&self) -> Doc<'_> {
+        self.as_slice().pretty()
+    }
+}
+
+impl<T: Pretty> Pretty for Vec<T> {
+    fn pretty(&self) -> Doc<'_> {
+        self.as_slice().pretty()
+    }
+}
+
+impl<T: Pretty> Pretty for VecDeque<T> {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("[", self.iter().map(Pretty::pretty), "]")
+    }
+}
+
+impl<T: Pretty> Pretty for BTreeSet<T> {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("{", self.iter().map(Pretty::pretty), "}")
+    }
+}
+
+impl<K: Pretty, V: Pretty> Pretty for BTreeMap<K, V> {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("{", self.iter().map(|(key, value)| entry(key, value)), "}")
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        Some(self.iter().map(|(key, value)| entry(key, value)).collect())
+    }
+}
+
+#[cfg(feature = "std")]
+/// Items are printed in iteration order of the set, which is unspecified.
+impl<T: Pretty, S> Pretty for std::collections::HashSet<T, S> {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("{", self.iter().map(Pretty::pretty), "}")
+    }
+}
+
+#[cfg(feature = "std")]
+/// Entries are printed in iteration order of the map, which is unspecified.
+impl<K: Pretty, V: Pretty, S> Pretty for std::collections::HashMap<K, V, S> {
+    fn pretty(&self) -> Doc<'_> {
+        sequence("{", self.iter().map(|(key, value)| entry(key, value)), "}")
+    }
+
+    fn __pretty_fields(&self) -> Option<Vec<Doc<'_>>> {
+        Some(self.iter().map(|(key, value)| entry(key, value)).collect())
+    }
+}
+
+impl<T: Pretty> Pretty for (T,) {
+    fn pretty(&self) -> Doc<'_> {
+        single_tuple(self.0.pretty())
+    }
+}
+
+macro_rules! impl_pretty_tuple {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: Pretty),+> Pretty for ($($t,)+) {
+            fn pretty(&self) -> Doc<'_> {
+                sequence("(", [$(self.$i.pretty()),+], ")")
+            }
+        }
+    };
+}
+
+impl_pretty_tuple!(A 0, B 1);
+impl_pretty_tuple!(A 0, B 1, C 2);
+impl_pretty_tuple!(A 0, B 1, C 2, D 3);
+impl_pretty_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_pretty_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_pretty_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_pretty_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
@@ -1,135 +1,574 @@
 use crate::{
     options::{LineBreak, PrintOptions},
+    validate::validate,
+    view::{Node, View},
     Doc, IndentKind,
 };
+use alloc::{
+    rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+pub(crate) type Map<K, V> = std::collections::HashMap<K, V>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+#[cfg(feature = "std")]
+pub(crate) type Set<T> = std::collections::HashSet<T>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Set<T> = alloc::collections::BTreeSet<T>;
+
+/// Docs that can be printed by [`print`].
+///
+/// This trait is sealed and only implemented by doc types of this crate.
+pub trait Printable: View {}
+
+impl Printable for Doc<'_> {}
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Mode {
     Flat,
     Break,
 }
 
-type Action<'a> = (usize, Mode, &'a Doc<'a>);
+/// The first component is the ID of indentation level in `Printer::indents`.
+type Action<'a, D> = (usize, Mode, &'a D);
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum IndentPart {
+    /// Printed as tabs if indentation kind is tab.
+    Indent,
+    /// Always printed as spaces.
+    Align,
+}
+
+/// Indentation is a list of parts, and each level is the last part of the list.
+/// Levels are interned, so actions can refer to them by ID.
+struct IndentLevel {
+    parent: usize,
+    part: IndentPart,
+    size: usize,
+    /// Total width of all parts.
+    width: usize,
+    /// Number of nests, which may be more than the number of parts since parts can be merged.
+    depth: usize,
+}
+
+/// Pending blank lines, nest depth where they're created,
+/// and the minimum nest depth since the last text or line break,
+/// which affect how blank lines are printed.
+type BlankLines = (usize, usize, usize);
+
+/// Doc, column, indentation, mode, blank lines,
+/// and anchors with current line if it's inside an alignment scope.
+type AttemptKey<D> = (
+    *const D,
+    usize,
+    usize,
+    Mode,
+    BlankLines,
+    Option<(usize, usize)>,
+);
+
+/// Result of successfully printing the first doc of union.
+#[derive(Clone)]
+struct Attempt<'a> {
+    output: Rc<str>,
+    cols: usize,
+    lines: usize,
+    blank_lines: BlankLines,
+    /// Anchors of the innermost alignment scope after printing.
+    anchors: Option<Anchors<'a>>,
+}
+
+/// Maximum number of measuring passes for each alignment scope.
+/// Padding anchors may change the layout, so it's measured again until columns are stable.
+/// Padding an anchor also moves anchors with other keys after it on the same line,
+/// so each key in such chain may need one more pass.
+const MAX_ALIGN_PASSES: usize = 8;
+
+/// Columns of alignment anchors in an alignment scope.
+struct AlignScope<'a> {
+    /// Columns that anchors should be padded to, measured by previous passes.
+    targets: Map<&'a str, usize>,
+    /// Anchors met in the current pass.
+    anchors: Anchors<'a>,
+}
+
+#[derive(Clone)]
+struct Anchors<'a> {
+    /// Rightmost columns reached by anchors before padding.
+    columns: Map<&'a str, usize>,
+    /// Last lines where anchors of each key occur.
+    /// Only the first anchor of a key on each line is aligned.
+    lines: Map<&'a str, usize>,
+    /// ID of this state, which changes whenever anchors are met,
+    /// so printing results that depend on anchors can be cached by it.
+    id: usize,
+}
 
 /// Pretty print a doc.
 ///
 /// ## Panics
 ///
 /// Panics if `options.tab_size` is `0`.
-pub fn print(doc: &Doc, options: &PrintOptions) -> String {
-    assert!(options.tab_size > 0);
+///
+/// When `debug_assertions` is enabled, it also panics if the doc is invalid.
+/// See [`Doc::validate`] for details.
+pub fn print(doc: &impl Printable, options: &PrintOptions) -> String {
+    check(doc, options);
 
     let mut printer = Printer::new(options);
+    printer.measure(doc);
     let mut out = String::with_capacity(1024);
-    printer.print_to((0, Mode::Break, doc), &mut out);
+    printer.print_to((0, Mode::Break, doc), &mut out, false);
+    printer.finish(&mut out);
     out
 }
 
-struct Printer<'a> {
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A line that exceeds [`width`](PrintOptions::width) limitation.
+pub struct Overflow {
+    /// Zero-based index of the line.
+    pub line: usize,
+    /// The actual width of the line.
+    pub width: usize,
+    /// The text which made this line exceed width limitation.
+    ///
+    /// This is `None` if the line exceeds because of indentation or spaces.
+    pub text: Option<String>,
+}
+
+/// Pretty print a doc, and report lines that exceed [`width`](PrintOptions::width) limitation.
+///
+/// ```
+/// use tiny_pretty::{print_with_overflows, Doc, Overflow, PrintOptions};
+///
+/// let doc = Doc::list(vec![
+///     Doc::text("a"),
+///     Doc::line_or_space(),
+///     Doc::text("very_long_word"),
+///     Doc::text(";"),
+///     Doc::line_or_space(),
+///     Doc::text("b"),
+/// ]).group();
+/// let (output, overflows) = print_with_overflows(&doc, &PrintOptions {
+///     width: 10,
+///     ..Default::default()
+/// });
+/// assert_eq!("a\nvery_long_word;\nb", output);
+/// assert_eq!(overflows, [Overflow {
+///     line: 1,
+///     width: 15,
+///     text: Some("very_long_word".into()),
+/// }]);
+/// ```
+///
+/// ## Panics
+///
+/// Same as [`print`].
+pub fn print_with_overflows(
+    doc: &impl Printable,
+    options: &PrintOptions,
+) -> (String, Vec<Overflow>) {
+    check(doc, options);
+
+    let mut printer = Printer::new(options);
+    printer.measure(doc);
+    printer.overflows = Some(vec![]);
+    let mut out = String::with_capacity(1024);
+    printer.print_to((0, Mode::Break, doc), &mut out, false);
+    printer.finish(&mut out);
+    (out, printer.overflows.unwrap_or_default())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// Statistics collected during printing, for finding out expensive docs.
+pub struct PrintStats {
+    /// How many times the printer checks if a group can be put on a single line.
+    pub fitting_calls: usize,
+    /// Total number of docs visited when checking if groups can be put on a single line.
+    pub fitting_nodes: usize,
+    /// How many times the printer tries printing the first doc of a [`union`](Doc::union).
+    pub union_attempts: usize,
+    /// How many times the first doc of a [`union`](Doc::union) exceeds width limitation,
+    /// so the printer has to print the alternate doc.
+    pub union_rollbacks: usize,
+    /// Total bytes of output that are thrown away because of rollbacks.
+    pub discarded_bytes: usize,
+    /// How many times the printer reuses the result of a previous attempt
+    /// on the same [`union`](Doc::union) at the same position.
+    pub union_cache_hits: usize,
+    /// Maximum depth of the internal action stack.
+    pub max_stack_depth: usize,
+}
+
+/// Pretty print a doc, and collect statistics during printing.
+///
+/// ```
+/// use tiny_pretty::{print_with_stats, Doc, PrintOptions};
+///
+/// let doc = Doc::text("aaaa")
+///     .append(Doc::hard_line())
+///     .append(Doc::text("bbbb"))
+///     .union(Doc::text("cc"));
+/// let (output, stats) = print_with_stats(&doc, &PrintOptions {
+///     width: 3,
+///     ..Default::default()
+/// });
+/// assert_eq!("cc", output);
+/// assert_eq!(stats.union_attempts, 1);
+/// assert_eq!(stats.union_rollbacks, 1);
+/// assert_eq!(stats.discarded_bytes, 4);
+/// ```
+///
+/// ## Panics
+///
+/// Same as [`print`].
+pub fn print_with_stats(doc: &impl Printable, options: &PrintOptions) -> (String, PrintStats) {
+    check(doc, options);
+
+    let mut printer = Printer::new(options);
+    printer.measure(doc);
+    printer.stats = Some(Default::default());
+    let mut out = String::with_capacity(1024);
+    printer.print_to((0, Mode::Break, doc), &mut out, false);
+    printer.finish(&mut out);
+    (out, printer.stats.unwrap_or_default())
+}
+
+/// Printing state which is kept between docs printed by streaming printer.
+#[derive(Default)]
+pub(crate) struct PrintState {
+    cols: usize,
+    line: usize,
+    blank_lines: BlankLines,
+    /// Whether there's any output except trailing line breaks.
+    has_output: bool,
+}
+
+/// Print the first `count` docs one after another, continuing from the given state.
+/// The rest docs aren't printed, but they're used for checking if groups can be put on
+/// current line.
+///
+/// If `finish` is `true`, pending blank lines and final line break will be handled at the end.
+pub(crate) fn print_stream<D: Printable>(
+    docs: &[D],
+    count: usize,
+    finish: bool,
+    options: &PrintOptions,
+    state: &mut PrintState,
+    out: &mut String,
+) {
+    docs[..count].iter().for_each(|doc| check(doc, options));
+
+    let mut printer = Printer::new(options);
+    docs.iter().for_each(|doc| printer.measure(doc));
+    printer.cols = state.cols;
+    printer.line = state.line;
+    printer.restore_blank_lines(state.blank_lines);
+    printer.has_output = state.has_output;
+
+    let actions = docs.iter().rev().map(|doc| (0, Mode::Break, doc)).collect();
+    printer.print_actions(actions, docs.len() - count, out, false);
+    if finish {
+        printer.finish(out);
+    }
+
+    state.cols = printer.cols;
+    state.line = printer.line;
+    state.blank_lines = printer.blank_lines_state();
+    state.has_output |= trailing_lines_start(out) > 0;
+}
+
+/// Find the start of trailing line breaks and indentation.
+pub(crate) fn trailing_lines_start(s: &str) -> usize {
+    let trimmed = s.trim_end_matches([' ', '\t', '\r', '\n']).len();
+    s[trimmed..]
+        .find(['\r', '\n'])
+        .map_or(s.len(), |index| trimmed + index)
+}
+
+fn check(doc: &impl Printable, options: &PrintOptions) {
+    assert!(options.tab_size > 0);
+    if cfg!(debug_assertions) {
+        let errors = validate(doc)
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "invalid doc: {}", errors.join("; "));
+    }
+}
+
+struct Printer<'a, D> {
     options: &'a PrintOptions,
     cols: usize,
+    line: usize,
+    overflows: Option<Vec<Overflow>>,
+    stats: Option<PrintStats>,
+    /// Blank lines that are waiting for the next line break to decide whether to print them.
+    blank_lines: usize,
+    /// Nest depth of the last empty line which creates pending blank lines.
+    blank_depth: usize,
+    /// The minimum nest depth reached since the last text or line break.
+    min_depth: usize,
+    /// Whether there's any output before, which isn't in the output buffer.
+    has_output: bool,
+    /// Results of attempting unions,
+    /// keyed by (doc, column, indentation, mode, blank lines, anchors).
+    /// Printing result of a doc only depends on these, so it's safe to reuse.
+    attempts: Map<AttemptKey<D>, Option<Attempt<'a>>>,
+    /// Widths of groups and lists when they're put on a single line.
+    /// `None` means it contains forced line breaks so it can't be put on a single line.
+    flat_widths: Map<*const D, Option<usize>>,
+    /// Stack of alignment scopes. Anchors belong to the innermost one.
+    align_scopes: Vec<AlignScope<'a>>,
+    /// Settled columns of anchors in alignment scopes, keyed by (doc, column, indentation, mode).
+    /// Outer scopes print inner scopes in every pass, and this avoids measuring them again.
+    align_targets: Map<(*const D, usize, usize, Mode), Map<&'a str, usize>>,
+    /// Number of created states of anchors, used for generating IDs.
+    anchors_count: usize,
+    /// IDs of anchor states, keyed by (previous ID, key, line, column) of the met anchor,
+    /// so meeting the same anchors in the same way results in the same state.
+    anchors_ids: Map<(usize, &'a str, usize, usize), usize>,
+    /// Interned indentation levels. The first one is the root level without indentation.
+    indents: Vec<IndentLevel>,
+    indent_ids: Map<(usize, IndentPart, usize, usize), usize>,
 }
 
-impl<'a> Printer<'a> {
+impl<'a, D: View> Printer<'a, D> {
     fn new(options: &'a PrintOptions) -> Self {
-        Self { options, cols: 0 }
+        Self {
+            options,
+            cols: 0,
+            line: 0,
+            overflows: None,
+            stats: None,
+            blank_lines: 0,
+            blank_depth: 0,
+            min_depth: 0,
+            has_output: false,
+            attempts: Map::new(),
+            flat_widths: Map::new(),
+            align_scopes: vec![],
+            align_targets: Map::new(),
+            anchors_count: 0,
+            anchors_ids: Map::new(),
+            indents: vec![IndentLevel {
+                parent: 0,
+                part: IndentPart::Indent,
+                size: 0,
+                width: 0,
+                depth: 0,
+            }],
+            indent_ids: Map::new(),
+        }
     }
 
-    fn print_to(&mut self, init_action: Action<'a>, out: &mut String) -> bool {
-        let line_break = match self.options.line_break {
-            LineBreak::Lf => "\n",
-            LineBreak::Crlf => "\r\n",
+    /// Measure widths of groups and lists in advance.
+    fn measure(&mut self, doc: &D) {
+        measure_flat_width(doc, &mut self.flat_widths);
+    }
+
+    /// Record current line if it exceeds width limitation.
+    fn check_overflow(&mut self, text: Option<&str>) {
+        if self.cols <= self.options.width {
+            return;
+        }
+        let Some(overflows) = &mut self.overflows else {
+            return;
         };
+        match overflows.last_mut() {
+            Some(overflow) if overflow.line == self.line => overflow.width = self.cols,
+            _ => overflows.push(Overflow {
+                line: self.line,
+                width: self.cols,
+                text: text.map(String::from),
+            }),
+        }
+    }
 
+    /// Try printing the first doc of union.
+    ///
+    /// Printer state will be restored after attempting,
+    /// so the result can be reused when printing the same doc with the same state.
+    fn attempt(&mut self, action: Action<'a, D>) -> Option<Attempt<'a>> {
+        let original_cols = self.cols;
+        let original_line = self.line;
+        let original_blank_lines = self.blank_lines_state();
+        let original_overflows = self.overflows.as_ref().map(Vec::len);
+        let original_anchors = self.align_scopes.last().map(|scope| scope.anchors.clone());
+
+        let mut buf = String::new();
+        let result = if self.print_to(action, &mut buf, true) {
+            Some(Attempt {
+                output: buf.into(),
+                cols: self.cols,
+                lines: self.line - original_line,
+                blank_lines: self.blank_lines_state(),
+                anchors: self.align_scopes.last().map(|scope| scope.anchors.clone()),
+            })
+        } else {
+            if let Some(stats) = &mut self.stats {
+                stats.discarded_bytes += buf.len();
+            }
+            // Anchors in discarded output shouldn't affect alignment.
+            if let (Some(scope), Some(anchors)) = (self.align_scopes.last_mut(), original_anchors) {
+                scope.anchors = anchors;
+            }
+            None
+        };
+
+        self.cols = original_cols;
+        self.line = original_line;
+        self.restore_blank_lines(original_blank_lines);
+        if let (Some(overflows), Some(len)) = (&mut self.overflows, original_overflows) {
+            overflows.truncate(len);
+        }
+        result
+    }
+
+    /// Print docs to `out`, and return whether it fits width limitation.
+    ///
+    /// If `bail` is `true`, it will stop printing once it exceeds width limitation.
+    fn print_to(&mut self, init_action: Action<'a, D>, out: &mut String, bail: bool) -> bool {
         let mut actions = Vec::with_capacity(128);
         actions.push(init_action);
+        self.print_actions(actions, 0, out, bail)
+    }
 
+    /// Print docs of actions to `out` until there're only `rest` actions left,
+    /// and return whether it fits width limitation.
+    ///
+    /// The rest actions aren't printed, but they're used for checking if groups fit.
+    fn print_actions(
+        &mut self,
+        mut actions: Vec<Action<'a, D>>,
+        rest: usize,
+        out: &mut String,
+        bail: bool,
+    ) -> bool {
         let mut fits = true;
 
-        while let Some((indent, mode, doc)) = actions.pop() {
-            match doc {
-                Doc::Nil => {}
-                Doc::Alt(doc_flat, doc_break) => match mode {
+        while actions.len() > rest
+            && let Some((indent, mode, doc)) = actions.pop()
+        {
+            if self.options.trim_blank_lines {
+                let depth = self.indents[indent].depth;
+                if depth < self.blank_depth {
+                    // Blank lines are at the end of nested block.
+                    self.blank_lines = 0;
+                }
+                self.min_depth = self.min_depth.min(depth);
+            }
+            if let Some(stats) = &mut self.stats {
+                stats.max_stack_depth = stats.max_stack_depth.max(actions.len() + 1);
+            }
+            match doc.view() {
+                Node::Nil => {}
+                Node::Alt(doc_flat, doc_break) => match mode {
                     Mode::Flat => actions.push((indent, mode, doc_flat)),
                     Mode::Break => actions.push((indent, mode, doc_break)),
                 },
-                Doc::Union(attempt, alternate) => {
-                    let original_cols = self.cols;
-
-                    let mut buf = String::new();
-                    if self.print_to((indent, mode, attempt), &mut buf) {
-                        // SAFETY: Both are `String`s.
-                        unsafe {
-                            out.as_mut_vec().append(buf.as_mut_vec());
+                Node::Union(attempt, alternate) => {
+                    if let Some(stats) = &mut self.stats {
+                        stats.union_attempts += 1;
+                    }
+                    let key = (
+                        attempt as *const _,
+                        self.cols,
+                        indent,
+                        mode,
+                        self.blank_lines_key(),
+                        // Inside alignment scopes, printing result also depends on anchors.
+                        self.align_scopes
+                            .last()
+                            .map(|scope| (scope.anchors.id, self.line)),
+                    );
+                    let result = if let Some(result) = self.attempts.get(&key) {
+                        if let Some(stats) = &mut self.stats {
+                            stats.union_cache_hits += 1;
+                        }
+                        result.clone()
+                    } else {
+                        let result = self.attempt((indent, mode, attempt));
+                        self.attempts.insert(key, result.clone());
+                        result
+                    };
+                    if let Some(result) = result {
+                        out.push_str(&result.output);
+                        self.cols = result.cols;
+                        self.line += result.lines;
+                        self.restore_blank_lines(result.blank_lines);
+                        if let (Some(scope), Some(anchors)) =
+                            (self.align_scopes.last_mut(), result.anchors)
+                        {
+                            scope.anchors = anchors;
                         }
                     } else {
-                        self.cols = original_cols;
+                        if let Some(stats) = &mut self.stats {
+                            stats.union_rollbacks += 1;
+                        }
                         actions.push((indent, mode, alternate));
                     }
                 }
-                Doc::Nest(offset, doc) => {
-                    actions.push((indent + offset, mode, doc));
+                Node::Nest(offset, doc) => {
+                    actions.push((self.indent(indent, IndentPart::Indent, offset), mode, doc));
+                }
+                Node::NestAlign(offset, doc) => {
+                    actions.push((self.indent(indent, IndentPart::Align, offset), mode, doc));
                 }
-                Doc::Text(text) => {
+                Node::Text(text) => {
+                    self.flush_blank_lines(out);
+                    self.min_depth = self.indents[indent].depth;
                     self.cols += measure_text_width(text);
                     out.push_str(text);
+                    self.check_overflow(Some(text));
                     fits &= self.cols <= self.options.width;
                 }
-                Doc::NewLine => {
-                    self.cols = indent;
-                    out.push_str(line_break);
-                    match self.options.indent_kind {
-                        IndentKind::Space => {
-                            out.push_str(&" ".repeat(indent));
-                        }
-                        IndentKind::Tab => {
-                            out.push_str(&"\t".repeat(indent / self.options.tab_size));
-                            out.push_str(&" ".repeat(indent % self.options.tab_size));
-                        }
-                    }
+                Node::NewLine => {
+                    self.new_line(out, indent);
+                    self.min_depth = self.indents[indent].depth;
+                    self.check_overflow(None);
                     fits &= self.cols <= self.options.width;
                 }
-                Doc::EmptyLine => {
-                    out.push_str(line_break);
+                Node::EmptyLine => {
+                    let depth = self.indents[indent].depth;
+                    // Blank lines at the start of nested block are dropped.
+                    if !self.options.trim_blank_lines || depth <= self.min_depth {
+                        self.blank_lines += 1;
+                        self.blank_depth = depth;
+                    }
                 }
-                Doc::Break(spaces, offset) => {
+                Node::Break(spaces, offset) => {
                     match mode {
                         Mode::Flat => {
+                            self.flush_blank_lines(out);
                             self.cols += spaces;
-                            out.push_str(&" ".repeat(*spaces));
+                            out.push_str(&" ".repeat(spaces));
                         }
                         Mode::Break => {
-                            self.cols = indent + offset;
-                            out.push_str(line_break);
-                            match self.options.indent_kind {
-                                IndentKind::Space => {
-                                    out.push_str(&" ".repeat(self.cols));
-                                }
-                                IndentKind::Tab => {
-                                    out.push_str(&"\t".repeat(self.cols / self.options.tab_size));
-                                    out.push_str(&" ".repeat(self.cols % self.options.tab_size));
-                                }
-                            }
+                            self.min_depth = self.indents[indent].depth;
+                            let indent = self.indent(indent, IndentPart::Indent, offset);
+                            self.new_line(out, indent);
                         }
                     };
+                    self.check_overflow(None);
                     fits &= self.cols <= self.options.width;
                 }
-                Doc::Group(docs) => match mode {
+                Node::Group(docs) => match mode {
                     Mode::Flat => {
                         actions.extend(docs.iter().map(|doc| (indent, Mode::Flat, doc)).rev());
                     }
                     Mode::Break => {
-                        let fitting_actions = docs
-                            .iter()
-                            .map(|doc| (indent, Mode::Flat, doc))
-                            .rev()
-                            .collect();
-                        let mode = if fitting(
-                            fitting_actions,
-                            actions.iter().rev(),
-                            self.cols,
-                            self.options.width,
-                        ) {
+                        let mode = if self.flat_widths.get(&(doc as *const _)) == Some(&None) {
+                            Mode::Break
+                        } else if self
+                            .fitting(vec![(indent, Mode::Flat, doc)], actions.iter().rev())
+                        {
                             Mode::Flat
                         } else {
                             Mode::Break
@@ -137,63 +576,427 @@ impl<'a> Printer<'a> {
                         actions.extend(docs.iter().map(|doc| (indent, mode, doc)).rev());
                     }
                 },
-                Doc::List(docs) => {
+                Node::List(docs) => {
                     actions.extend(docs.iter().map(|doc| (indent, mode, doc)).rev());
                 }
+                Node::AlignAnchor(key) => {
+                    if let Some(scope) = self.align_scopes.last_mut()
+                        && scope.anchors.lines.insert(key, self.line) != Some(self.line)
+                    {
+                        let count = &mut self.anchors_count;
+                        scope.anchors.id = *self
+                            .anchors_ids
+                            .entry((scope.anchors.id, key, self.line, self.cols))
+                            .or_insert_with(|| {
+                                *count += 1;
+                                *count
+                            });
+                        let column = scope.anchors.columns.entry(key).or_insert(0);
+                        *column = (*column).max(self.cols);
+                        if let Some(&target) = scope.targets.get(key)
+                            && target > self.cols
+                        {
+                            self.flush_blank_lines(out);
+                            out.push_str(&" ".repeat(target - self.cols));
+                            self.cols = target;
+                            self.check_overflow(None);
+                            fits &= self.cols <= self.options.width;
+                        }
+                    }
+                }
+                Node::AlignScope(doc) => {
+                    fits &= self.print_align_scope((indent, mode, doc), &actions, out, bail);
+                }
+            }
+            if bail && !fits {
+                return false;
             }
         }
 
         fits
     }
-}
 
-/// Check if a group can be placed on single line.
-///
-/// There's no magic here:
-/// it just simply attempts to put the whole group and the rest actions into current line.
-/// After that, if current column is still less than width limitation,
-/// we can feel sure that this group can be put on current line without line breaks.
-fn fitting<'a>(
-    mut actions: Vec<Action<'a>>,
-    mut best_actions: impl Iterator<Item = &'a Action<'a>>,
-    mut cols: usize,
-    width: usize,
-) -> bool {
-    while let Some((indent, mode, doc)) = actions.pop().or_else(|| best_actions.next().copied()) {
-        match doc {
-            Doc::Nil => {}
-            Doc::Alt(doc_flat, doc_break) => match mode {
-                Mode::Flat => actions.push((indent, mode, doc_flat)),
-                Mode::Break => actions.push((indent, mode, doc_break)),
-            },
-            Doc::Union(attempt, alternate) => match mode {
-                Mode::Flat => actions.push((indent, mode, attempt)),
-                Mode::Break => actions.push((indent, mode, alternate)),
-            },
-            Doc::Nest(offset, doc) => {
-                actions.push((indent + offset, mode, doc));
+    fn line_break(&self) -> &'static str {
+        match self.options.line_break {
+            LineBreak::Lf => "\n",
+            LineBreak::Crlf => "\r\n",
+        }
+    }
+
+    /// Get the ID of indentation level after adding a part to the given level.
+    ///
+    /// Adjacent parts of the same kind are merged,
+    /// so nested indentations are printed as the same as a single one.
+    fn indent(&mut self, id: usize, part: IndentPart, offset: usize) -> usize {
+        if offset == 0 {
+            return id;
+        }
+        let level = &self.indents[id];
+        let depth = level.depth + 1;
+        let (parent, size) = if id != 0 && level.part == part {
+            (level.parent, level.size + offset)
+        } else {
+            (id, offset)
+        };
+        let indents = &mut self.indents;
+        *self
+            .indent_ids
+            .entry((parent, part, size, depth))
+            .or_insert_with(|| {
+                let width = indents[parent].width + size;
+                indents.push(IndentLevel {
+                    parent,
+                    part,
+                    size,
+                    width,
+                    depth,
+                });
+                indents.len() - 1
+            })
+    }
+
+    /// Print a line break with indentation, and print pending blank lines before it.
+    fn new_line(&mut self, out: &mut String, indent: usize) {
+        let width = self.indents[indent].width;
+        self.flush_blank_lines(out);
+
+        self.cols = width;
+        self.line += 1;
+        out.push_str(self.line_break());
+        match self.options.indent_kind {
+            IndentKind::Space => {
+                out.push_str(&" ".repeat(width));
             }
-            Doc::Text(text) => {
-                cols += measure_text_width(text);
+            IndentKind::Tab => {
+                let mut parts = vec![];
+                let mut id = indent;
+                while id != 0 {
+                    let level = &self.indents[id];
+                    parts.push((level.part, level.size));
+                    id = level.parent;
+                }
+                // Only leading indentation is printed as tabs,
+                // since tabs after spaces won't be displayed as expected at different tab width.
+                let mut aligned = false;
+                for (part, size) in parts.into_iter().rev() {
+                    aligned |= part == IndentPart::Align;
+                    if aligned {
+                        out.push_str(&" ".repeat(size));
+                    } else {
+                        out.push_str(&"\t".repeat(size / self.options.tab_size));
+                        out.push_str(&" ".repeat(size % self.options.tab_size));
+                    }
+                }
             }
-            Doc::Break(spaces, _) => match mode {
-                Mode::Flat => cols += spaces,
-                Mode::Break => return true,
-            },
-            Doc::NewLine => {
-                // https://github.com/Marwes/pretty.rs/blob/83021205d557d77731d404cd40b37b105ab762c7/src/render.rs#L381
-                return matches!(mode, Mode::Break);
+        }
+    }
+
+    /// Print pending blank lines, but no more than the maximum number of blank lines.
+    fn flush_blank_lines(&mut self, out: &mut String) {
+        let mut blank_lines = core::mem::take(&mut self.blank_lines);
+        if let Some(max) = self.options.max_blank_lines {
+            blank_lines = blank_lines.min(max);
+        }
+        self.line += blank_lines;
+        for _ in 0..blank_lines {
+            out.push_str(self.line_break());
+        }
+    }
+
+    fn blank_lines_state(&self) -> BlankLines {
+        (self.blank_lines, self.blank_depth, self.min_depth)
+    }
+
+    fn restore_blank_lines(&mut self, (blank_lines, blank_depth, min_depth): BlankLines) {
+        self.blank_lines = blank_lines;
+        self.blank_depth = blank_depth;
+        self.min_depth = min_depth;
+    }
+
+    /// Only states that affect the printing result are used as part of cache key.
+    fn blank_lines_key(&self) -> BlankLines {
+        if self.options.trim_blank_lines {
+            self.blank_lines_state()
+        } else {
+            (self.blank_lines, 0, 0)
+        }
+    }
+
+    /// Print pending blank lines at the end of output, and handle final line break.
+    fn finish(&mut self, out: &mut String) {
+        if self.options.trim_blank_lines && self.blank_depth > 0 {
+            // The end of output leaves all nests.
+            self.blank_lines = 0;
+        }
+        if self.options.final_newline {
+            self.blank_lines = 0;
+            // Drop trailing blank lines and indentation, then add a single line break.
+            out.truncate(trailing_lines_start(out));
+            if self.has_output || !out.is_empty() {
+                out.push_str(self.line_break());
+            }
+        } else {
+            self.flush_blank_lines(out);
+        }
+    }
+
+    /// Print docs in an alignment scope.
+    ///
+    /// Columns of anchors are measured by printing the scope in advance,
+    /// then it's printed again with anchors padded to measured columns.
+    /// This is repeated until no anchors go beyond measured columns,
+    /// and the output of last pass is used directly.
+    ///
+    /// Measured columns never decrease between passes,
+    /// so layout changes caused by padding can't make it oscillate.
+    ///
+    /// The rest actions after the scope are only used for checking if groups fit.
+    fn print_align_scope(
+        &mut self,
+        action: Action<'a, D>,
+        rest: &[Action<'a, D>],
+        out: &mut String,
+        bail: bool,
+    ) -> bool {
+        let original_cols = self.cols;
+        let original_line = self.line;
+        let original_blank_lines = self.blank_lines_state();
+        let original_overflows = self.overflows.as_ref().map(Vec::len);
+
+        let (indent, mode, doc) = action;
+        let key = (doc as *const _, self.cols, indent, mode);
+        let mut targets = self.align_targets.get(&key).cloned().unwrap_or_default();
+        for pass in 1.. {
+            self.anchors_count += 1;
+            self.align_scopes.push(AlignScope {
+                targets,
+                anchors: Anchors {
+                    columns: Map::new(),
+                    lines: Map::new(),
+                    id: self.anchors_count,
+                },
+            });
+            let mut buf = String::new();
+            let mut actions = rest.to_vec();
+            actions.push(action);
+            let fits = self.print_actions(actions, rest.len(), &mut buf, bail);
+            let scope = self
+                .align_scopes
+                .pop()
+                .expect("alignment scope should exist");
+            let stable = scope.anchors.columns.iter().all(|(key, column)| {
+                scope
+                    .targets
+                    .get(key)
+                    .is_some_and(|target| column <= target)
+            });
+            if stable {
+                self.align_targets.insert(key, scope.targets);
+                out.push_str(&buf);
+                return fits;
+            }
+            if bail && !fits {
+                out.push_str(&buf);
+                return fits;
             }
-            Doc::EmptyLine => {}
-            Doc::Group(docs) | Doc::List(docs) => {
-                actions.extend(docs.iter().map(|doc| (indent, mode, doc)).rev());
+            assert!(
+                pass < MAX_ALIGN_PASSES,
+                "columns of alignment anchors can't be settled"
+            );
+
+            if let Some(stats) = &mut self.stats {
+                stats.discarded_bytes += buf.len();
+            }
+            self.cols = original_cols;
+            self.line = original_line;
+            self.restore_blank_lines(original_blank_lines);
+            if let (Some(overflows), Some(len)) = (&mut self.overflows, original_overflows) {
+                overflows.truncate(len);
+            }
+            targets = scope.targets;
+            for (key, column) in scope.anchors.columns {
+                let target = targets.entry(key).or_insert(0);
+                *target = (*target).max(column);
             }
         }
-        if cols > width {
-            return false;
+        unreachable!()
+    }
+
+    /// Check if a group can be placed on single line.
+    ///
+    /// There's no magic here:
+    /// it just simply attempts to put the whole group and the rest actions into current line.
+    /// After that, if current column is still less than width limitation,
+    /// we can feel sure that this group can be put on current line without line breaks.
+    ///
+    /// Groups and lists in flat mode are measured in advance,
+    /// so they're skipped as a whole instead of visiting their children.
+    fn fitting<'b>(
+        &mut self,
+        mut actions: Vec<Action<'a, D>>,
+        mut best_actions: impl Iterator<Item = &'b Action<'a, D>>,
+    ) -> bool
+    where
+        'a: 'b,
+    {
+        if let Some(stats) = &mut self.stats {
+            stats.fitting_calls += 1;
+        }
+        let mut cols = self.cols;
+        let mut visited = 0;
+        let mut in_rest = false;
+        // Keys of anchors met on current line, which won't be aligned again.
+        let mut anchored = vec![];
+        while let Some((indent, mode, doc)) = actions.pop().or_else(|| {
+            in_rest = true;
+            best_actions.next().copied()
+        }) {
+            if let (true, Some(lookahead)) = (in_rest, self.options.lookahead) {
+                if visited == lookahead {
+                    // Give up checking: assume the rest fits.
+                    return true;
+                }
+                visited += 1;
+            }
+            if let Some(stats) = &mut self.stats {
+                stats.fitting_nodes += 1;
+            }
+            match doc.view() {
+                Node::Nil => {}
+                Node::Alt(doc_flat, doc_break) => match mode {
+                    Mode::Flat => actions.push((indent, mode, doc_flat)),
+                    Mode::Break => actions.push((indent, mode, doc_break)),
+                },
+                Node::Union(attempt, alternate) => match mode {
+                    Mode::Flat => actions.push((indent, mode, attempt)),
+                    Mode::Break => actions.push((indent, mode, alternate)),
+                },
+                // Indentation doesn't matter here, since checking stops at line breaks.
+                Node::Nest(_, doc) | Node::NestAlign(_, doc) => {
+                    actions.push((indent, mode, doc));
+                }
+                Node::Text(text) => {
+                    cols += measure_text_width(text);
+                }
+                Node::Break(spaces, _) => match mode {
+                    Mode::Flat => cols += spaces,
+                    Mode::Break => return true,
+                },
+                Node::NewLine => {
+                    // https://github.com/Marwes/pretty.rs/blob/83021205d557d77731d404cd40b37b105ab762c7/src/render.rs#L381
+                    return matches!(mode, Mode::Break);
+                }
+                Node::EmptyLine => {}
+                Node::AlignAnchor(key) => {
+                    if let Some(scope) = self.align_scopes.last()
+                        && scope.anchors.lines.get(key) != Some(&self.line)
+                        && !anchored.contains(&key)
+                    {
+                        anchored.push(key);
+                        if let Some(&target) = scope.targets.get(key) {
+                            cols = cols.max(target);
+                        }
+                    }
+                }
+                Node::AlignScope(doc) => {
+                    actions.push((indent, mode, doc));
+                }
+                Node::Group(docs) | Node::List(docs) => match mode {
+                    Mode::Flat => match self.flat_widths.get(&(doc as *const _)) {
+                        Some(Some(width)) => cols += width,
+                        Some(None) => return false,
+                        None => actions.extend(docs.iter().map(|doc| (indent, mode, doc)).rev()),
+                    },
+                    Mode::Break => {
+                        actions.extend(docs.iter().map(|doc| (indent, mode, doc)).rev());
+                    }
+                },
+            }
+            if cols > self.options.width {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Measure the width of doc when it's put on a single line.
+///
+/// Return `None` if it contains forced line breaks.
+pub(crate) fn flat_width(doc: &impl View) -> Option<usize> {
+    measure_flat_width(doc, &mut Map::default())
+}
+
+/// Measure the width of doc when it's put on a single line,
+/// and cache widths of groups and lists.
+///
+/// Return `None` if it contains forced line breaks.
+///
+/// Docs can be deeply nested, so it uses an explicit stack instead of recursion.
+/// Widths of groups and lists containing alignment anchors aren't cached,
+/// because padding of anchors can only be known when printing.
+fn measure_flat_width<D: View>(doc: &D, cache: &mut Map<*const D, Option<usize>>) -> Option<usize> {
+    enum Step<'d, D> {
+        Measure(&'d D),
+        /// Keep the width of the first doc of `Alt` or `Union`,
+        /// since the second doc is only measured for caching.
+        Pair,
+        /// Sum up widths of children of a group or list.
+        Sum(&'d D, usize),
+    }
+
+    let mut steps = vec![Step::Measure(doc)];
+    // Measured widths, and whether docs contain alignment anchors.
+    let mut widths: Vec<(Option<usize>, bool)> = vec![];
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Measure(doc) => match doc.view() {
+                Node::Nil | Node::EmptyLine => widths.push((Some(0), false)),
+                Node::Alt(first, second) | Node::Union(first, second) => {
+                    steps.push(Step::Pair);
+                    steps.push(Step::Measure(first));
+                    steps.push(Step::Measure(second));
+                }
+                Node::Nest(_, doc) | Node::NestAlign(_, doc) | Node::AlignScope(doc) => {
+                    steps.push(Step::Measure(doc));
+                }
+                Node::Text(text) => widths.push((Some(measure_text_width(text)), false)),
+                Node::NewLine => widths.push((None, false)),
+                Node::Break(spaces, _) => widths.push((Some(spaces), false)),
+                Node::AlignAnchor(..) => widths.push((Some(0), true)),
+                Node::Group(docs) | Node::List(docs) => {
+                    if let Some(width) = cache.get(&(doc as *const _)) {
+                        widths.push((*width, false));
+                    } else {
+                        // Don't stop early when there're forced line breaks,
+                        // since the rest docs still need to be measured.
+                        steps.push(Step::Sum(doc, docs.len()));
+                        steps.extend(docs.iter().rev().map(Step::Measure));
+                    }
+                }
+            },
+            Step::Pair => {
+                let (width, first_anchored) = widths.pop().expect("width of first doc");
+                let (_, second_anchored) = widths.pop().expect("width of second doc");
+                widths.push((width, first_anchored || second_anchored));
+            }
+            Step::Sum(doc, len) => {
+                let (width, anchored) = widths.drain(widths.len() - len..).fold(
+                    (Some(0usize), false),
+                    |(width, anchored), (doc_width, doc_anchored)| {
+                        let width = width.zip(doc_width).map(|(a, b)| a.saturating_add(b));
+                        (width, anchored || doc_anchored)
+                    },
+                );
+                // It can't be put on a single line anyway if there're forced line breaks.
+                if !anchored || width.is_none() {
+                    cache.insert(doc as *const _, width);
+                }
+                widths.push((width, anchored));
+            }
         }
     }
-    true
+    widths.pop().and_then(|(width, _)| width)
 }
 
 #[cfg(not(feature = "unicode-width"))]
@@ -0,0 +1,189 @@
+use crate::Doc;
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+#[derive(Clone, Debug, Default)]
+/// Options for [`Doc::reflow_with`].
+pub struct ReflowOptions {
+    /// Keep inline code spans like `` `a b` `` as a single word,
+    /// so they won't be split into different lines.
+    ///
+    /// Default value is `false`.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions, ReflowOptions};
+    ///
+    /// let options = PrintOptions { width: 10, ..Default::default() };
+    /// let reflow_options = ReflowOptions { protect_code_spans: true, ..Default::default() };
+    ///
+    /// let doc = Doc::reflow("call `foo(a, b)` now");
+    /// assert_eq!("call\n`foo(a,\nb)` now", &print(&doc, &options));
+    ///
+    /// let doc = Doc::reflow_with("call `foo(a, b)` now", &reflow_options);
+    /// assert_eq!("call\n`foo(a, b)`\nnow", &print(&doc, &options));
+    /// ```
+    pub protect_code_spans: bool,
+
+    /// Keep Markdown inline links like `[some text](url)` as a single word,
+    /// so they won't be split into different lines.
+    ///
+    /// Default value is `false`.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions, ReflowOptions};
+    ///
+    /// let options = PrintOptions { width: 10, ..Default::default() };
+    /// let reflow_options = ReflowOptions { protect_links: true, ..Default::default() };
+    ///
+    /// let doc = Doc::reflow_with("see [the docs](https://docs.rs)", &reflow_options);
+    /// assert_eq!("see\n[the docs](https://docs.rs)", &print(&doc, &options));
+    /// ```
+    pub protect_links: bool,
+}
+
+impl<'a> Doc<'a> {
+    /// Turn text into fillable paragraphs.
+    ///
+    /// Words are split by whitespaces (including single line breaks),
+    /// then joined with [`soft_line`](Doc::soft_line), so each line will be filled with
+    /// words as many as possible. Blank lines between paragraphs are kept.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let text = "The quick brown fox
+    /// jumps over the lazy dog.
+    ///
+    /// Pack my box with five dozen liquor jugs.";
+    /// assert_eq!(r#"
+    /// The quick brown fox jumps
+    /// over the lazy dog.
+    ///
+    /// Pack my box with five
+    /// dozen liquor jugs."#.trim(), &print(&Doc::reflow(text), &PrintOptions {
+    ///     width: 25,
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    pub fn reflow(text: &'a str) -> Doc<'a> {
+        Doc::reflow_with(text, &Default::default())
+    }
+
+    /// Same as [`reflow`](Doc::reflow), but with the given options
+    /// for protecting some parts of text from being split.
+    pub fn reflow_with(text: &'a str, options: &ReflowOptions) -> Doc<'a> {
+        let mut docs = Vec::new();
+        let mut line_breaks = None;
+        let mut start = None;
+        let mut index = 0;
+        while let Some(c) = text[index..].chars().next() {
+            if c.is_whitespace() {
+                if let Some(start) = start.take() {
+                    if let Some(line_breaks) = line_breaks.take() {
+                        push_separator(&mut docs, line_breaks);
+                    }
+                    docs.push(Doc::text(word(&text[start..index])));
+                    line_breaks = Some(0);
+                }
+                if c == '\n' {
+                    line_breaks = line_breaks.map(|line_breaks| line_breaks + 1);
+                }
+                index += c.len_utf8();
+                continue;
+            }
+
+            start.get_or_insert(index);
+            let protected = match c {
+                '`' if options.protect_code_spans => code_span(&text[index..]),
+                '[' if options.protect_links => link(&text[index..]),
+                _ => None,
+            };
+            index += protected
+                .filter(|span| !has_blank_line(span))
+                .map_or(c.len_utf8(), str::len);
+        }
+        if let Some(start) = start {
+            if let Some(line_breaks) = line_breaks {
+                push_separator(&mut docs, line_breaks);
+            }
+            docs.push(Doc::text(word(&text[start..])));
+        }
+        Doc::list(docs)
+    }
+}
+
+/// Push separator between two words.
+/// The number of blank lines is counted by line breaks between words.
+fn push_separator(docs: &mut Vec<Doc>, line_breaks: usize) {
+    if line_breaks < 2 {
+        docs.push(Doc::soft_line());
+    } else {
+        docs.extend((1..line_breaks).map(|_| Doc::empty_line()));
+        docs.push(Doc::hard_line());
+    }
+}
+
+/// Protected spans may contain whitespaces, which will be collapsed into a single space.
+fn word(s: &str) -> Cow<'_, str> {
+    if s.contains(char::is_whitespace) {
+        let mut word = String::with_capacity(s.len());
+        for (i, part) in s.split_whitespace().enumerate() {
+            if i > 0 {
+                word.push(' ');
+            }
+            word.push_str(part);
+        }
+        Cow::Owned(word)
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Match code span at the start of input, which is surrounded by backticks of the same length.
+fn code_span(input: &str) -> Option<&str> {
+    let len = input.len() - input.trim_start_matches('`').len();
+    let mut index = len;
+    while let Some(offset) = input[index..].find('`') {
+        let start = index + offset;
+        let run = input[start..].len() - input[start..].trim_start_matches('`').len();
+        if run == len {
+            return Some(&input[..start + run]);
+        }
+        index = start + run;
+    }
+    None
+}
+
+/// Match Markdown inline link like `[text](url)` at the start of input.
+fn link(input: &str) -> Option<&str> {
+    let text_end = matching(input, '[', ']')?;
+    let rest = &input[text_end..];
+    if !rest.starts_with('(') {
+        return None;
+    }
+    let url_end = matching(rest, '(', ')')?;
+    Some(&input[..text_end + url_end])
+}
+
+/// Find the end position of the closing delimiter which matches the opening one at the start.
+fn matching(input: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in input.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i + c.len_utf8());
+            }
+        }
+    }
+    None
+}
+
+/// Spans across paragraphs shouldn't be protected.
+fn has_blank_line(s: &str) -> bool {
+    let mut lines = s.split('\n');
+    lines.next();
+    lines.next_back();
+    lines.any(|line| line.trim().is_empty())
+}
@@ -0,0 +1,166 @@
+use crate::Doc;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Controls when to insert separator after the last item of [`Sequence`].
+pub enum TrailingSeparator {
+    /// Never insert trailing separator.
+    #[default]
+    Never,
+    /// Always insert trailing separator, even if the sequence is on a single line.
+    Always,
+    /// Insert trailing separator only when the sequence is broken into different lines.
+    IfBroken,
+}
+
+#[derive(Clone, Debug)]
+/// Builder of delimited sequences, such as arrays, argument lists and tuples.
+///
+/// Items are put on a single line if possible; otherwise each item is placed on its own line
+/// with indentation.
+///
+/// ```
+/// use tiny_pretty::{print, Doc, PrintOptions, Sequence, TrailingSeparator};
+///
+/// let sequence = Sequence::new(Doc::text("["), Doc::text("]"))
+///     .trailing(TrailingSeparator::IfBroken);
+/// let doc = sequence.build(["a", "b", "c"].into_iter().map(Doc::text));
+///
+/// assert_eq!("[a, b, c]", &print(&doc, &Default::default()));
+/// assert_eq!("[\n  a,\n  b,\n  c,\n]", &print(&doc, &PrintOptions {
+///     width: 5,
+///     ..Default::default()
+/// }));
+///
+/// let doc = sequence.build([]);
+/// assert_eq!("[]", &print(&doc, &Default::default()));
+/// ```
+pub struct Sequence<'a> {
+    open: Doc<'a>,
+    close: Doc<'a>,
+    separator: Doc<'a>,
+    trailing: TrailingSeparator,
+    padding: bool,
+    empty: Option<Doc<'a>>,
+    indent: usize,
+}
+
+impl<'a> Sequence<'a> {
+    /// Create a sequence builder with the given delimiters.
+    ///
+    /// By default, the separator is `,`, there's no trailing separator and no padding,
+    /// empty sequence is printed as delimiters only, and the indentation size is 2.
+    pub fn new(open: Doc<'a>, close: Doc<'a>) -> Self {
+        Self {
+            open,
+            close,
+            separator: Doc::text(","),
+            trailing: TrailingSeparator::Never,
+            padding: false,
+            empty: None,
+            indent: 2,
+        }
+    }
+
+    /// Set the separator between items.
+    /// Line break or space will be inserted after the separator automatically.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, Sequence};
+    ///
+    /// let doc = Sequence::new(Doc::text("("), Doc::text(")"))
+    ///     .separator(Doc::text(" |"))
+    ///     .build([Doc::text("a"), Doc::text("b")]);
+    /// assert_eq!("(a | b)", &print(&doc, &Default::default()));
+    /// ```
+    pub fn separator(mut self, separator: Doc<'a>) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Set when to insert separator after the last item.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, Sequence, TrailingSeparator};
+    ///
+    /// let doc = Sequence::new(Doc::text("("), Doc::text(")"))
+    ///     .trailing(TrailingSeparator::Always)
+    ///     .build([Doc::text("a")]);
+    /// assert_eq!("(a,)", &print(&doc, &Default::default()));
+    /// ```
+    pub fn trailing(mut self, trailing: TrailingSeparator) -> Self {
+        self.trailing = trailing;
+        self
+    }
+
+    /// Set whether to insert spaces after open delimiter and before close delimiter
+    /// when the sequence is on a single line.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, Sequence};
+    ///
+    /// let doc = Sequence::new(Doc::text("{"), Doc::text("}"))
+    ///     .padding(true)
+    ///     .build([Doc::text("a"), Doc::text("b")]);
+    /// assert_eq!("{ a, b }", &print(&doc, &Default::default()));
+    /// ```
+    pub fn padding(mut self, padding: bool) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the doc to be used when there're no items,
+    /// instead of printing delimiters only.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, Sequence};
+    ///
+    /// let doc = Sequence::new(Doc::text("{"), Doc::text("}"))
+    ///     .empty(Doc::text("{ /* empty */ }"))
+    ///     .build([]);
+    /// assert_eq!("{ /* empty */ }", &print(&doc, &Default::default()));
+    /// ```
+    pub fn empty(mut self, empty: Doc<'a>) -> Self {
+        self.empty = Some(empty);
+        self
+    }
+
+    /// Set the indentation size of items when the sequence is broken into different lines.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Build the doc of sequence with the given items.
+    pub fn build(&self, items: impl IntoIterator<Item = Doc<'a>>) -> Doc<'a> {
+        let mut items = items.into_iter().peekable();
+        if items.peek().is_none() {
+            return match &self.empty {
+                Some(empty) => empty.clone(),
+                None => self.open.clone().append(self.close.clone()),
+            };
+        }
+
+        let line = if self.padding {
+            Doc::line_or_space
+        } else {
+            Doc::line_or_nil
+        };
+        let mut inner = line().append(Doc::join(
+            items,
+            self.separator.clone().append(Doc::line_or_space()),
+        ));
+        match self.trailing {
+            TrailingSeparator::Never => {}
+            TrailingSeparator::Always => inner = inner.append(self.separator.clone()),
+            TrailingSeparator::IfBroken => {
+                inner = inner.append(Doc::flat_or_break(Doc::nil(), self.separator.clone()));
+            }
+        }
+        self.open
+            .clone()
+            .append(inner.nest(self.indent))
+            .append(line())
+            .append(self.close.clone())
+            .group()
+    }
+}
@@ -0,0 +1,652 @@
+use crate::{
+    pretty::{single_tuple, INDENT},
+    Doc, Sequence, TrailingSeparator,
+};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Write};
+use serde::ser::{self, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Output syntax of [`to_doc`].
+pub enum SerializeStyle {
+    /// Standard JSON. Arrays and objects don't have trailing commas.
+    ///
+    /// Map keys must be strings, chars, numbers or booleans,
+    /// and non-string keys will be quoted.
+    /// Non-finite floats will be serialized as `null`.
+    #[default]
+    Json,
+    /// JSON5, which is JSON with trailing commas when arrays and objects are broken
+    /// into different lines. Field names of structs are unquoted
+    /// unless they aren't valid identifiers.
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use tiny_pretty::{print, to_doc, SerializeStyle};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Header {
+    ///     #[serde(rename = "content-type")]
+    ///     content_type: &'static str,
+    ///     #[serde(rename = "1st")]
+    ///     first: bool,
+    ///     _id: u8,
+    /// }
+    ///
+    /// let header = Header { content_type: "text/plain", first: true, _id: 0 };
+    /// let doc = to_doc(&header, SerializeStyle::Json5).unwrap();
+    /// assert_eq!(
+    ///     r#"{"content-type": "text/plain", "1st": true, _id: 0}"#,
+    ///     print(&doc, &Default::default()),
+    /// );
+    /// ```
+    Json5,
+    /// Rusty Object Notation, which looks like Rust literals,
+    /// with struct names, enum variant names and trailing commas.
+    Ron,
+}
+
+/// Serialize a value into doc with the given style.
+///
+/// Arrays, objects and structs are put on a single line if possible;
+/// otherwise each item is placed on its own line with indentation.
+///
+/// ```
+/// use serde::Serialize;
+/// use tiny_pretty::{print, to_doc, PrintOptions, SerializeStyle};
+///
+/// #[derive(Serialize)]
+/// struct Config {
+///     name: &'static str,
+///     ports: Vec<u16>,
+///     mode: Option<Mode>,
+/// }
+///
+/// #[derive(Serialize)]
+/// enum Mode {
+///     Fast,
+/// }
+///
+/// let config = Config { name: "server", ports: vec![80, 443], mode: Some(Mode::Fast) };
+/// let options = PrintOptions { width: 30, ..Default::default() };
+///
+/// let doc = to_doc(&config, SerializeStyle::Json).unwrap();
+/// assert_eq!(r#"
+/// {
+///     "name": "server",
+///     "ports": [80, 443],
+///     "mode": "Fast"
+/// }"#.trim(), print(&doc, &options));
+///
+/// let doc = to_doc(&config, SerializeStyle::Json5).unwrap();
+/// assert_eq!(r#"
+/// {
+///     name: "server",
+///     ports: [80, 443],
+///     mode: "Fast",
+/// }"#.trim(), print(&doc, &options));
+///
+/// let doc = to_doc(&config, SerializeStyle::Ron).unwrap();
+/// assert_eq!(r#"
+/// Config(
+///     name: "server",
+///     ports: [80, 443],
+///     mode: Some(Fast),
+/// )"#.trim(), print(&doc, &options));
+///
+/// let doc = to_doc(&vec![(1, 'a')], SerializeStyle::Ron).unwrap();
+/// assert_eq!("[(1, 'a')]", print(&doc, &options));
+/// ```
+pub fn to_doc<T: Serialize + ?Sized>(
+    value: &T,
+    style: SerializeStyle,
+) -> Result<Doc<'static>, SerializeError> {
+    value.serialize(DocSerializer::new(style))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Error returned by [`to_doc`] and [`DocSerializer`].
+pub struct SerializeError(String);
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl core::error::Error for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerializeError(msg.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+/// Serde serializer whose output is doc. See [`to_doc`] for details.
+pub struct DocSerializer {
+    style: SerializeStyle,
+}
+
+impl DocSerializer {
+    /// Create a serializer with the given style.
+    ///
+    /// The style decides the output syntax, such as quoting of keys, trailing commas
+    /// and struct names. Serializing with it is the same as calling [`to_doc`].
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use tiny_pretty::{print, DocSerializer, SerializeStyle};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let point = Point { x: 1, y: 2 };
+    ///
+    /// let doc = point.serialize(DocSerializer::new(SerializeStyle::Json)).unwrap();
+    /// assert_eq!(r#"{"x": 1, "y": 2}"#, print(&doc, &Default::default()));
+    ///
+    /// let doc = point.serialize(DocSerializer::new(SerializeStyle::Json5)).unwrap();
+    /// assert_eq!("{x: 1, y: 2}", print(&doc, &Default::default()));
+    ///
+    /// let doc = point.serialize(DocSerializer::new(SerializeStyle::Ron)).unwrap();
+    /// assert_eq!("Point(x: 1, y: 2)", print(&doc, &Default::default()));
+    /// ```
+    pub fn new(style: SerializeStyle) -> Self {
+        Self { style }
+    }
+
+    fn is_json(&self) -> bool {
+        matches!(self.style, SerializeStyle::Json | SerializeStyle::Json5)
+    }
+
+    fn string(&self, s: &str) -> Doc<'static> {
+        if self.is_json() {
+            let mut escaped = String::with_capacity(s.len() + 2);
+            escaped.push('"');
+            for c in s.chars() {
+                match c {
+                    '"' => escaped.push_str("\\\""),
+                    '\\' => escaped.push_str("\\\\"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    '\t' => escaped.push_str("\\t"),
+                    '\u{8}' => escaped.push_str("\\b"),
+                    '\u{c}' => escaped.push_str("\\f"),
+                    c if c.is_control() => {
+                        let _ = write!(escaped, "\\u{:04x}", c as u32);
+                    }
+                    c => escaped.push(c),
+                }
+            }
+            escaped.push('"');
+            Doc::text(escaped)
+        } else {
+            Doc::text(format!("{s:?}"))
+        }
+    }
+
+    fn float(&self, value: f64, debug: String) -> Doc<'static> {
+        if value.is_finite() || self.style == SerializeStyle::Ron {
+            Doc::text(debug)
+        } else if self.style == SerializeStyle::Json {
+            Doc::text("null")
+        } else if value.is_nan() {
+            Doc::text("NaN")
+        } else if value.is_sign_positive() {
+            Doc::text("Infinity")
+        } else {
+            Doc::text("-Infinity")
+        }
+    }
+
+    fn sequence(
+        &self,
+        open: &'static str,
+        docs: Vec<Doc<'static>>,
+        close: &'static str,
+    ) -> Doc<'static> {
+        let trailing = if self.style == SerializeStyle::Json {
+            TrailingSeparator::Never
+        } else {
+            TrailingSeparator::IfBroken
+        };
+        Sequence::new(Doc::text(open), Doc::text(close))
+            .trailing(trailing)
+            .indent(INDENT)
+            .build(docs)
+    }
+
+    /// Wrap doc of enum variant as an object with the variant name as the only key.
+    fn json_variant(&self, variant: &'static str, doc: Doc<'static>) -> Doc<'static> {
+        self.sequence("{", vec![entry(self.string(variant), doc)], "}")
+    }
+
+    fn compound(&self, kind: CompoundKind, len: Option<usize>) -> Compound {
+        Compound {
+            serializer: *self,
+            kind,
+            docs: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        }
+    }
+}
+
+/// Check if a field name can be used as JSON5 key without quotes.
+///
+/// Non-ASCII identifiers are valid in ECMAScript, but they're quoted for simplicity.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn entry(key: Doc<'static>, value: Doc<'static>) -> Doc<'static> {
+    key.append(Doc::text(": ")).append(value)
+}
+
+impl ser::Serializer for DocSerializer {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(if v { "true" } else { "false" }))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(self.float(v.into(), format!("{v:?}")))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(self.float(v, format!("{v:?}")))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        if self.is_json() {
+            Ok(self.string(v.encode_utf8(&mut [0; 4])))
+        } else {
+            Ok(Doc::text(format!("{v:?}")))
+        }
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(self.string(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let docs = v.iter().map(|byte| Doc::text(byte.to_string())).collect();
+        Ok(self.sequence("[", docs, "]"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(if self.is_json() { "null" } else { "None" }))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let doc = value.serialize(self)?;
+        if self.is_json() {
+            Ok(doc)
+        } else {
+            Ok(self.sequence("Some(", vec![doc], ")"))
+        }
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Doc::text(if self.is_json() { "null" } else { "()" }))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        if self.is_json() {
+            Ok(Doc::text("null"))
+        } else {
+            Ok(Doc::text(name))
+        }
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        if self.is_json() {
+            Ok(self.string(variant))
+        } else {
+            Ok(Doc::text(variant))
+        }
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let doc = value.serialize(self)?;
+        if self.is_json() {
+            Ok(doc)
+        } else {
+            Ok(Doc::text(name).append(self.sequence("(", vec![doc], ")")))
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let doc = value.serialize(self)?;
+        if self.is_json() {
+            Ok(self.json_variant(variant, doc))
+        } else {
+            Ok(Doc::text(variant).append(self.sequence("(", vec![doc], ")")))
+        }
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self.compound(CompoundKind::Seq, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self.compound(CompoundKind::Tuple, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self.compound(CompoundKind::TupleStruct(name), Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(self.compound(CompoundKind::TupleVariant(variant), Some(len)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self.compound(CompoundKind::Map, len))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self.compound(CompoundKind::Struct(name), Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self.compound(CompoundKind::StructVariant(variant), Some(len)))
+    }
+}
+
+enum CompoundKind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str),
+    Map,
+    Struct(&'static str),
+    StructVariant(&'static str),
+}
+
+#[doc(hidden)]
+/// Serializer of sequences, tuples, maps and structs. Created by [`DocSerializer`].
+pub struct Compound {
+    serializer: DocSerializer,
+    kind: CompoundKind,
+    docs: Vec<Doc<'static>>,
+    key: Option<Doc<'static>>,
+}
+
+impl Compound {
+    fn push_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        self.docs.push(value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn push_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        let key = match self.serializer.style {
+            SerializeStyle::Json => self.serializer.string(key),
+            SerializeStyle::Json5 if !is_identifier(key) => self.serializer.string(key),
+            _ => Doc::text(key),
+        };
+        let value = value.serialize(self.serializer)?;
+        self.docs.push(entry(key, value));
+        Ok(())
+    }
+
+    fn finish(self) -> Doc<'static> {
+        let serializer = self.serializer;
+        let docs = self.docs;
+        if serializer.is_json() {
+            match self.kind {
+                CompoundKind::Seq | CompoundKind::Tuple | CompoundKind::TupleStruct(..) => {
+                    serializer.sequence("[", docs, "]")
+                }
+                CompoundKind::Map | CompoundKind::Struct(..) => serializer.sequence("{", docs, "}"),
+                CompoundKind::TupleVariant(variant) => {
+                    serializer.json_variant(variant, serializer.sequence("[", docs, "]"))
+                }
+                CompoundKind::StructVariant(variant) => {
+                    serializer.json_variant(variant, serializer.sequence("{", docs, "}"))
+                }
+            }
+        } else {
+            match self.kind {
+                CompoundKind::Seq => serializer.sequence("[", docs, "]"),
+                CompoundKind::Tuple if docs.len() == 1 => {
+                    single_tuple(docs.into_iter().next().unwrap_or_else(Doc::nil))
+                }
+                CompoundKind::Tuple => serializer.sequence("(", docs, ")"),
+                CompoundKind::Map => serializer.sequence("{", docs, "}"),
+                CompoundKind::TupleStruct(name)
+                | CompoundKind::TupleVariant(name)
+                | CompoundKind::Struct(name)
+                | CompoundKind::StructVariant(name) => {
+                    Doc::text(name).append(serializer.sequence("(", docs, ")"))
+                }
+            }
+        }
+    }
+}
+
+impl ser::SerializeSeq for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeMap for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = key.serialize(self.serializer)?;
+        self.key = Some(if self.serializer.is_json() {
+            json_key(key)?
+        } else {
+            key
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value is serialized before key"))?;
+        let value = value.serialize(self.serializer)?;
+        self.docs.push(entry(key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Make sure the serialized map key is a JSON string, quoting numbers and booleans.
+fn json_key(key: Doc<'static>) -> Result<Doc<'static>, SerializeError> {
+    match key {
+        Doc::Text(Cow::Borrowed("null")) => Err(ser::Error::custom("key must be a string")),
+        Doc::Text(text) if text.starts_with('"') => Ok(Doc::Text(text)),
+        Doc::Text(text) => Ok(Doc::text(format!("\"{text}\""))),
+        _ => Err(ser::Error::custom("key must be a string")),
+    }
+}
+
+impl ser::SerializeStruct for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for Compound {
+    type Ok = Doc<'static>;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    print::{print_stream, trailing_lines_start, PrintState},
+    view::{Node, View},
+    Doc, PrintOptions,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// Printer that prints docs one after another and writes output to a sink as soon as possible,
+/// so there's no need to build a giant doc for large output.
+///
+/// Docs pushed to this printer are printed as if they're concatenated into a single doc.
+/// Pushed docs are kept until another doc containing line breaks is pushed,
+/// because whether groups can be put on a single line depends on the docs after them
+/// until the next line break.
+/// After that, finished output is written to the sink,
+/// and only the last doc is kept for checking if groups can be put on current line.
+///
+/// ```
+/// use tiny_pretty::{print, Doc, PrintOptions, StreamPrinter};
+///
+/// let items = ["first", "second", "third"];
+/// let options = PrintOptions { width: 20, ..Default::default() };
+///
+/// let mut printer = StreamPrinter::new(String::new(), &options);
+/// for item in items {
+///     printer.push(Doc::text("call(").append(Doc::text(item)).append(Doc::text(");")))?;
+///     printer.push(Doc::hard_line())?;
+/// }
+/// let output = printer.finish()?;
+/// assert_eq!("call(first);\ncall(second);\ncall(third);\n", output);
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub struct StreamPrinter<'a, W: fmt::Write> {
+    out: W,
+    options: PrintOptions,
+    state: PrintState,
+    pending: Vec<Doc<'a>>,
+    /// Output that hasn't been written to the sink yet.
+    buf: String,
+}
+
+impl<'a, W: fmt::Write> StreamPrinter<'a, W> {
+    /// Create a streaming printer which writes output to `out`.
+    pub fn new(out: W, options: &PrintOptions) -> Self {
+        Self {
+            out,
+            options: options.clone(),
+            state: Default::default(),
+            pending: Vec::new(),
+            buf: String::new(),
+        }
+    }
+
+    /// Push a doc to be printed after previous docs.
+    ///
+    /// This returns an error if writing to the sink fails.
+    ///
+    /// ```
+    /// use tiny_pretty::{Doc, PrintOptions, StreamPrinter};
+    ///
+    /// let options = PrintOptions { width: 10, ..Default::default() };
+    /// let mut printer = StreamPrinter::new(String::new(), &options);
+    /// let group = Doc::text("aaaa")
+    ///     .append(Doc::line_or_space())
+    ///     .append(Doc::text("bbbb"))
+    ///     .group();
+    /// printer.push(group)?;
+    /// // The group will be broken because of docs after it.
+    /// printer.push(Doc::text("cccc"))?;
+    /// assert_eq!("aaaa\nbbbbcccc", printer.finish()?);
+    /// # Ok::<(), std::fmt::Error>(())
+    /// ```
+    pub fn push(&mut self, doc: Doc<'a>) -> fmt::Result {
+        let breaks_line = breaks_line(&doc);
+        self.pending.push(doc);
+        if breaks_line {
+            self.flush(self.pending.len() - 1, false)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Push docs from an iterator. See [`push`](StreamPrinter::push) for details.
+    ///
+    /// ```
+    /// use tiny_pretty::{Doc, StreamPrinter};
+    ///
+    /// let mut printer = StreamPrinter::new(String::new(), &Default::default());
+    /// printer.extend((0..3).map(|i| Doc::text(i.to_string()).append(Doc::hard_line())))?;
+    /// assert_eq!("0\n1\n2\n", printer.finish()?);
+    /// # Ok::<(), std::fmt::Error>(())
+    /// ```
+    pub fn extend(&mut self, docs: impl IntoIterator<Item = Doc<'a>>) -> fmt::Result {
+        docs.into_iter().try_for_each(|doc| self.push(doc))
+    }
+
+    /// Print all the rest docs, then return the sink.
+    pub fn finish(mut self) -> Result<W, fmt::Error> {
+        self.flush(self.pending.len(), true)?;
+        Ok(self.out)
+    }
+
+    /// Print the first `count` pending docs and write output to the sink.
+    fn flush(&mut self, count: usize, finish: bool) -> fmt::Result {
+        print_stream(
+            &self.pending,
+            count,
+            finish,
+            &self.options,
+            &mut self.state,
+            &mut self.buf,
+        );
+        self.pending.drain(..count);
+
+        // Trailing line breaks may be removed when ensuring final line break,
+        // so they're kept until more output comes.
+        let len = if self.options.final_newline && !finish {
+            trailing_lines_start(&self.buf)
+        } else {
+            self.buf.len()
+        };
+        self.out.write_str(&self.buf[..len])?;
+        self.buf.drain(..len);
+        Ok(())
+    }
+}
+
+/// Check if a top-level doc contains line breaks in "break" mode.
+/// Checking whether groups fit always stops at such doc, so docs after it don't matter.
+fn breaks_line(doc: &impl View) -> bool {
+    match doc.view() {
+        Node::Nil | Node::Text(..) | Node::EmptyLine | Node::AlignAnchor(..) => false,
+        Node::NewLine | Node::Break(..) => true,
+        // Only the "break" doc and the alternate doc are checked in "break" mode.
+        Node::Alt(_, doc) | Node::Union(_, doc) => breaks_line(doc),
+        Node::Nest(_, doc) | Node::NestAlign(_, doc) | Node::AlignScope(doc) => breaks_line(doc),
+        Node::Group(docs) | Node::List(docs) => docs.iter().any(breaks_line),
+    }
+}
+
+#[cfg(feature = "std")]
+/// Adapter for using [`std::io::Write`] as the sink of [`StreamPrinter`].
+///
+/// When writing fails, [`StreamPrinter`] only returns [`fmt::Error`];
+/// the underlying I/O error can be retrieved by [`into_inner`](IoWriter::into_inner).
+///
+/// ```
+/// use tiny_pretty::{Doc, IoWriter, StreamPrinter};
+///
+/// let mut printer = StreamPrinter::new(IoWriter::new(Vec::new()), &Default::default());
+/// printer.push(Doc::text("hello"))?;
+/// let bytes = printer.finish()?.into_inner().unwrap();
+/// assert_eq!(b"hello", &bytes[..]);
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub struct IoWriter<W: std::io::Write> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> IoWriter<W> {
+    /// Wrap an I/O writer.
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Get the underlying writer back,
+    /// or the first I/O error which occurred when writing.
+    pub fn into_inner(self) -> std::io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.inner),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
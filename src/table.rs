@@ -0,0 +1,171 @@
+use crate::{print::flat_width, Doc};
+use alloc::{vec, vec::Vec};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Alignment of cells in a column of [`Table`].
+pub enum Alignment {
+    /// Pad cells with spaces on the right.
+    #[default]
+    Left,
+    /// Pad cells with spaces on the left.
+    Right,
+    /// Pad cells on both sides, with the extra space on the right.
+    Center,
+}
+
+#[derive(Clone, Debug)]
+/// Builder of tables whose cells are aligned across rows.
+///
+/// Width of each column is the maximum flat width of cells in that column,
+/// and cells are padded with spaces to match the column width.
+/// Rows containing forced line breaks don't take part in computing column widths.
+/// When a row can't be put on a single line, it falls back to be printed without padding,
+/// and cells in that row can be broken into different lines as usual.
+///
+/// ```
+/// use tiny_pretty::{print, Alignment, Doc, PrintOptions, Table};
+///
+/// let table = Table::new()
+///     .separator(Doc::text(" | "))
+///     .align(1, Alignment::Right);
+/// let doc = table.build(vec![
+///     vec![Doc::text("apple"), Doc::text("1")],
+///     vec![Doc::text("banana"), Doc::text("200")],
+///     vec![Doc::text("kiwi"), Doc::text("30")],
+/// ]);
+/// assert_eq!(r#"
+/// apple  |   1
+/// banana | 200
+/// kiwi   |  30"#.trim(), &print(&doc, &Default::default()));
+/// ```
+pub struct Table<'a> {
+    separator: Doc<'a>,
+    alignments: Vec<Alignment>,
+}
+
+impl Default for Table<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Table<'a> {
+    /// Create a table builder whose cells are separated by a space and aligned to left.
+    pub fn new() -> Self {
+        Self {
+            separator: Doc::space(),
+            alignments: vec![],
+        }
+    }
+
+    /// Set the separator between cells.
+    pub fn separator(mut self, separator: Doc<'a>) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Set alignment of the column at the given index.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Alignment, Doc, Table};
+    ///
+    /// let doc = Table::new()
+    ///     .align(0, Alignment::Center)
+    ///     .build(vec![
+    ///         vec![Doc::text("a"), Doc::text("=")],
+    ///         vec![Doc::text("abcde"), Doc::text("=")],
+    ///     ]);
+    /// assert_eq!("  a   =\nabcde =", &print(&doc, &Default::default()));
+    /// ```
+    pub fn align(mut self, column: usize, alignment: Alignment) -> Self {
+        if self.alignments.len() <= column {
+            self.alignments.resize(column + 1, Alignment::Left);
+        }
+        self.alignments[column] = alignment;
+        self
+    }
+
+    /// Build the doc of table with the given rows.
+    /// Rows are separated by hard line breaks.
+    pub fn build(&self, rows: impl IntoIterator<Item = Vec<Doc<'a>>>) -> Doc<'a> {
+        let rows = rows
+            .into_iter()
+            .map(|cells| {
+                let widths = cells.iter().map(flat_width).collect::<Option<Vec<_>>>();
+                (cells, widths)
+            })
+            .collect::<Vec<_>>();
+
+        let mut column_widths = Vec::<usize>::new();
+        for widths in rows.iter().filter_map(|(_, widths)| widths.as_ref()) {
+            if column_widths.len() < widths.len() {
+                column_widths.resize(widths.len(), 0);
+            }
+            column_widths
+                .iter_mut()
+                .zip(widths)
+                .for_each(|(column_width, width)| *column_width = (*column_width).max(*width));
+        }
+
+        let rows = rows.into_iter().map(|(cells, widths)| {
+            let Some(widths) = widths else {
+                return Doc::join(cells, self.separator.clone());
+            };
+            let last = cells.len().saturating_sub(1);
+            let padded = cells
+                .iter()
+                .zip(widths)
+                .zip(&column_widths)
+                .enumerate()
+                .map(|(i, ((cell, width), column_width))| {
+                    let padding = column_width - width;
+                    let (left, right) = match self.alignments.get(i).copied().unwrap_or_default() {
+                        Alignment::Left => (0, padding),
+                        Alignment::Right => (padding, 0),
+                        Alignment::Center => (padding / 2, padding - padding / 2),
+                    };
+                    // Don't leave trailing spaces at the end of line.
+                    let right = if i == last { 0 } else { right };
+                    spaces(left).append(cell.clone()).append(spaces(right))
+                });
+            let padded = Doc::join(padded, self.separator.clone());
+            Doc::flat_or_break(padded, Doc::join(cells, self.separator.clone())).group()
+        });
+        Doc::join(rows, Doc::hard_line())
+    }
+}
+
+fn spaces<'a>(count: usize) -> Doc<'a> {
+    if count == 0 {
+        Doc::nil()
+    } else {
+        Doc::text(" ".repeat(count))
+    }
+}
+
+impl<'a> Doc<'a> {
+    /// Create a table with default options. Each row is a list of cells.
+    /// See [`Table`] for details and more options.
+    ///
+    /// ```
+    /// use tiny_pretty::{print, Doc, PrintOptions};
+    ///
+    /// let doc = Doc::table(vec![
+    ///     vec![Doc::text("let"), Doc::text("a"), Doc::text("= 1;")],
+    ///     vec![Doc::text("let"), Doc::text("long_name"), Doc::text("= 2;")],
+    /// ]);
+    /// assert_eq!("let a         = 1;\nlet long_name = 2;", &print(&doc, &Default::default()));
+    ///
+    /// // The second row can't be put on a single line, so it falls back to be printed
+    /// // without padding, and the first row is aligned with the third row only.
+    /// let doc = Doc::table(vec![
+    ///     vec![Doc::text("a"), Doc::text("b")],
+    ///     vec![Doc::text("c"), Doc::text("d").append(Doc::hard_line()).append(Doc::text("e"))],
+    ///     vec![Doc::text("fff"), Doc::text("g")],
+    /// ]);
+    /// assert_eq!("a   b\nc d\ne\nfff g", &print(&doc, &PrintOptions::default()));
+    /// ```
+    pub fn table(rows: impl IntoIterator<Item = Vec<Doc<'a>>>) -> Doc<'a> {
+        Table::new().build(rows)
+    }
+}
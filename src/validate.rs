@@ -0,0 +1,237 @@
+use crate::{
+    print::{Map, Set},
+    view::{Node, View},
+    Doc,
+};
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A structural problem found by [`Doc::validate`].
+pub struct ValidationIssue {
+    /// Position of the problematic node in the doc tree.
+    ///
+    /// Each element is the index of a child node:
+    /// for lists and groups it's the index in the list,
+    /// for [`flat_or_break`](Doc::flat_or_break) and [`union`](Doc::union) it's `0` for
+    /// the first doc and `1` for the second doc, and for [`nest`](Doc::nest) it's always `0`.
+    /// An empty path refers to the root doc.
+    pub path: Vec<usize>,
+    /// What the problem is.
+    pub kind: ValidationIssueKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+/// Kind of [`ValidationIssue`].
+pub enum ValidationIssueKind {
+    /// The text contains `\n`, which breaks column computation.
+    TextContainsLineBreak,
+    /// The group contains nothing but nils, so grouping is meaningless.
+    EmptyGroup,
+    /// The attempt of a union never exceeds width limitation,
+    /// so the alternate can never be chosen.
+    UnreachableAlternate,
+    /// Accumulated indentation overflows `usize`.
+    NestOverflow,
+}
+
+impl ValidationIssue {
+    /// Check if this issue will make the printed result incorrect.
+    ///
+    /// Other issues are harmless for printing, but they usually indicate bugs in doc builders.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.kind,
+            ValidationIssueKind::TextContainsLineBreak | ValidationIssueKind::NestOverflow
+        )
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            ValidationIssueKind::TextContainsLineBreak => "text contains line break",
+            ValidationIssueKind::EmptyGroup => "group contains nothing",
+            ValidationIssueKind::UnreachableAlternate => "alternate of union can never be chosen",
+            ValidationIssueKind::NestOverflow => "indentation overflows",
+        };
+        write!(f, "{message} at {:?}", self.path)
+    }
+}
+
+impl Doc<'_> {
+    /// Walk the whole doc tree and report structural problems.
+    ///
+    /// When `debug_assertions` is enabled, [`print`](crate::print) will run this automatically
+    /// and panic if any [errors](ValidationIssue::is_error) are found.
+    ///
+    /// Docs shared by multiple parents are only checked once with the same indentation,
+    /// so issues inside them are only reported at the first path.
+    ///
+    /// ```
+    /// use tiny_pretty::{Doc, ValidationIssueKind};
+    ///
+    /// let doc = Doc::list(vec![Doc::text("a\nb"), Doc::nil().group()]);
+    /// let issues = doc.validate();
+    /// assert_eq!(issues.len(), 2);
+    /// assert_eq!(issues[0].path, [0]);
+    /// assert_eq!(issues[0].kind, ValidationIssueKind::TextContainsLineBreak);
+    /// assert_eq!(issues[1].path, [1]);
+    /// assert_eq!(issues[1].kind, ValidationIssueKind::EmptyGroup);
+    ///
+    /// let doc = Doc::text("code").append(Doc::line_or_space()).group();
+    /// assert!(doc.validate().is_empty());
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validate(self)
+    }
+}
+
+pub(crate) fn validate<D: View>(doc: &D) -> Vec<ValidationIssue> {
+    let mut validator = Validator {
+        path: vec![],
+        issues: vec![],
+        visited: Set::new(),
+        can_exceed: Map::new(),
+    };
+    validator.visit(doc);
+    validator.issues
+}
+
+struct Validator<D> {
+    path: Vec<usize>,
+    issues: Vec<ValidationIssue>,
+    /// Visited docs with indentation, so shared docs won't be visited again and again.
+    visited: Set<(*const D, usize)>,
+    /// Cached results of [`can_exceed`](Validator::can_exceed).
+    can_exceed: Map<*const D, bool>,
+}
+
+/// Docs can be deeply nested, so they're visited with an explicit stack instead of recursion.
+enum Step<'d, D> {
+    /// Visit a doc with its index in the parent (if it isn't the root) and indentation.
+    Enter(Option<usize>, &'d D, usize),
+    /// Leave a non-root doc, so its index will be removed from the path.
+    Leave,
+}
+
+impl<D: View> Validator<D> {
+    fn visit(&mut self, doc: &D) {
+        let mut steps = vec![Step::Enter(None, doc, 0)];
+        while let Some(step) = steps.pop() {
+            let Step::Enter(index, doc, indent) = step else {
+                self.path.pop();
+                continue;
+            };
+            if !self.visited.insert((doc as *const _, indent)) {
+                continue;
+            }
+            if let Some(index) = index {
+                self.path.push(index);
+                steps.push(Step::Leave);
+            }
+            let mut visit_child =
+                |index: usize, doc, indent| steps.push(Step::Enter(Some(index), doc, indent));
+            // Children are pushed in reverse order, so they'll be visited in order.
+            match doc.view() {
+                Node::Nil | Node::NewLine | Node::EmptyLine | Node::AlignAnchor(..) => {}
+                Node::Alt(doc_flat, doc_break) => {
+                    visit_child(1, doc_break, indent);
+                    visit_child(0, doc_flat, indent);
+                }
+                Node::Union(attempt, alternate) => {
+                    if !self.can_exceed(attempt) {
+                        self.report(ValidationIssueKind::UnreachableAlternate);
+                    }
+                    visit_child(1, alternate, indent);
+                    visit_child(0, attempt, indent);
+                }
+                Node::Nest(offset, doc) | Node::NestAlign(offset, doc) => {
+                    match indent.checked_add(offset) {
+                        Some(indent) => visit_child(0, doc, indent),
+                        None => self.report(ValidationIssueKind::NestOverflow),
+                    }
+                }
+                Node::Text(text) => {
+                    if text.contains('\n') {
+                        self.report(ValidationIssueKind::TextContainsLineBreak);
+                    }
+                }
+                Node::Break(_, offset) => {
+                    if indent.checked_add(offset).is_none() {
+                        self.report(ValidationIssueKind::NestOverflow);
+                    }
+                }
+                Node::Group(docs) => {
+                    if docs.iter().all(|doc| matches!(doc.view(), Node::Nil)) {
+                        self.report(ValidationIssueKind::EmptyGroup);
+                    }
+                    docs.iter()
+                        .enumerate()
+                        .rev()
+                        .for_each(|(i, doc)| visit_child(i, doc, indent));
+                }
+                Node::List(docs) => {
+                    docs.iter()
+                        .enumerate()
+                        .rev()
+                        .for_each(|(i, doc)| visit_child(i, doc, indent));
+                }
+                Node::AlignScope(doc) => visit_child(0, doc, indent),
+            }
+        }
+    }
+
+    fn report(&mut self, kind: ValidationIssueKind) {
+        self.issues.push(ValidationIssue {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    /// Check if a doc contains anything that may make the printer exceed width limitation.
+    ///
+    /// Results of all visited docs are cached, so shared docs are only checked once.
+    fn can_exceed(&mut self, doc: &D) -> bool {
+        // The flag tells whether children have been checked.
+        let mut docs = vec![(doc, false)];
+        while let Some((doc, checked)) = docs.pop() {
+            let ptr = doc as *const _;
+            if self.can_exceed.contains_key(&ptr) {
+                continue;
+            }
+            let result = match doc.view() {
+                Node::Nil | Node::EmptyLine => false,
+                Node::Text(..) | Node::NewLine | Node::Break(..) | Node::AlignAnchor(..) => true,
+                node if !checked => {
+                    docs.push((doc, true));
+                    match node {
+                        Node::Alt(a, b) | Node::Union(a, b) => {
+                            docs.extend([(a, false), (b, false)]);
+                        }
+                        Node::Nest(_, doc) | Node::NestAlign(_, doc) | Node::AlignScope(doc) => {
+                            docs.push((doc, false));
+                        }
+                        Node::Group(children) | Node::List(children) => {
+                            docs.extend(children.iter().map(|doc| (doc, false)));
+                        }
+                        _ => unreachable!(),
+                    }
+                    continue;
+                }
+                Node::Alt(a, b) | Node::Union(a, b) => {
+                    self.can_exceed[&(a as *const _)] || self.can_exceed[&(b as *const _)]
+                }
+                Node::Nest(_, doc) | Node::NestAlign(_, doc) | Node::AlignScope(doc) => {
+                    self.can_exceed[&(doc as *const _)]
+                }
+                Node::Group(children) | Node::List(children) => children
+                    .iter()
+                    .any(|doc| self.can_exceed[&(doc as *const _)]),
+            };
+            self.can_exceed.insert(ptr, result);
+        }
+        self.can_exceed[&(doc as *const _)]
+    }
+}
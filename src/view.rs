@@ -0,0 +1,23 @@
+/// Borrowed view of a single doc node.
+///
+/// Different doc representations provide this view,
+/// so they can share the same printing algorithm.
+pub enum Node<'d, D> {
+    Nil,
+    Alt(&'d D, &'d D),
+    Union(&'d D, &'d D),
+    Nest(usize, &'d D),
+    NestAlign(usize, &'d D),
+    Text(&'d str),
+    NewLine,
+    EmptyLine,
+    Break(usize, usize),
+    Group(&'d [D]),
+    List(&'d [D]),
+    AlignAnchor(&'d str),
+    AlignScope(&'d D),
+}
+
+pub trait View: Sized {
+    fn view(&self) -> Node<'_, Self>;
+}
@@ -0,0 +1,357 @@
+//! Derive macro of the `Pretty` trait for [tiny_pretty](https://docs.rs/tiny_pretty).
+//!
+//! Don't use this crate directly; instead, enable the `derive` feature gate of tiny_pretty.
+//!
+//! Derived implementations lay out values like Rust struct literals:
+//! they're put on a single line if possible; otherwise each field is placed on its own line
+//! with indentation and trailing comma.
+//!
+//! ```
+//! use tiny_pretty::Pretty;
+//!
+//! #[derive(Pretty)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! #[derive(Pretty)]
+//! enum Shape {
+//!     Circle(Point, u32),
+//!     Polygon { points: Vec<Point> },
+//!     Empty,
+//! }
+//!
+//! let shape = Shape::Polygon {
+//!     points: vec![Point { x: 0, y: 0 }, Point { x: 1, y: 2 }],
+//! };
+//! assert_eq!(
+//!     "Polygon { points: [Point { x: 0, y: 0 }, Point { x: 1, y: 2 }] }",
+//!     shape.to_pretty_string(80),
+//! );
+//! assert_eq!(r#"
+//! Polygon {
+//!     points: [
+//!         Point { x: 0, y: 0 },
+//!         Point { x: 1, y: 2 },
+//!     ],
+//! }"#.trim(), shape.to_pretty_string(30));
+//!
+//! let shape = Shape::Circle(Point { x: 0, y: 0 }, 1);
+//! assert_eq!("Circle(Point { x: 0, y: 0 }, 1)", shape.to_pretty_string(80));
+//! assert_eq!("Empty", Shape::Empty.to_pretty_string(80));
+//! ```
+//!
+//! ## Attributes
+//!
+//! - `#[pretty(rename = "name")]` on structs, enum variants and named fields:
+//!   Print with the given name instead of the Rust identifier.
+//! - `#[pretty(skip)]` on fields: Don't print this field.
+//! - `#[pretty(flatten)]` on named fields: Inline fields of this field into the parent.
+//!   The field type should be a struct with named fields that derives `Pretty`, or a map.
+//! - `#[pretty(with = "path")]` on fields: Build doc of this field with the given function,
+//!   whose signature should be `fn(&T) -> Doc<'_>`.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use tiny_pretty::{Doc, Pretty};
+//!
+//! #[derive(Pretty)]
+//! #[pretty(rename = "config")]
+//! struct Config {
+//!     #[pretty(rename = "type")]
+//!     kind: String,
+//!     #[pretty(skip)]
+//!     cache: Vec<u8>,
+//!     #[pretty(flatten)]
+//!     extra: BTreeMap<&'static str, bool>,
+//!     #[pretty(with = "hex")]
+//!     color: u32,
+//! }
+//!
+//! fn hex(value: &u32) -> Doc<'_> {
+//!     Doc::text(format!("#{value:06x}"))
+//! }
+//!
+//! let config = Config {
+//!     kind: "dark".into(),
+//!     cache: vec![0; 1024],
+//!     extra: BTreeMap::from([("bold", true)]),
+//!     color: 0xff8800,
+//! };
+//! assert_eq!(
+//!     r#"config { type: "dark", "bold": true, color: #ff8800 }"#,
+//!     config.to_pretty_string(80),
+//! );
+//! ```
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields,
+    GenericParam, LitStr, Path, Result,
+};
+
+#[proc_macro_derive(Pretty, attributes(pretty))]
+pub fn derive_pretty(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let name = parse_name_options(&input.attrs)?.unwrap_or_else(|| ident.unraw().to_string());
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let accessors = data
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| match &field.ident {
+                    Some(ident) => quote! { &self.#ident },
+                    None => {
+                        let index = syn::Index::from(i);
+                        quote! { &self.#index }
+                    }
+                })
+                .collect::<Vec<_>>();
+            let doc = expand_fields(&name, &data.fields, &accessors)?;
+            let pretty_fields = if let Fields::Named(..) = data.fields {
+                let fields = expand_named_fields(&data.fields, &accessors)?;
+                Some(quote! {
+                    fn __pretty_fields(&self) -> ::core::option::Option<::tiny_pretty::__private::Vec<::tiny_pretty::Doc<'_>>> {
+                        ::core::option::Option::Some(#fields)
+                    }
+                })
+            } else {
+                None
+            };
+            quote! {
+                fn pretty(&self) -> ::tiny_pretty::Doc<'_> {
+                    #doc
+                }
+
+                #pretty_fields
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let name = parse_name_options(&variant.attrs)?
+                        .unwrap_or_else(|| ident.unraw().to_string());
+                    let bindings = variant
+                        .fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, field)| match &field.ident {
+                            Some(ident) => quote! { #ident },
+                            None => {
+                                let binding = format_ident!("__{}", i);
+                                quote! { #binding }
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    let doc = expand_fields(&name, &variant.fields, &bindings)?;
+                    let pattern = match &variant.fields {
+                        Fields::Named(..) => quote! { Self::#ident { #(#bindings,)* } },
+                        Fields::Unnamed(..) => quote! { Self::#ident(#(#bindings,)*) },
+                        Fields::Unit => quote! { Self::#ident },
+                    };
+                    Ok(quote! { #[allow(unused_variables)] #pattern => #doc, })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // Empty enums can't be matched by reference.
+            let scrutinee = if arms.is_empty() {
+                quote! { *self }
+            } else {
+                quote! { self }
+            };
+            quote! {
+                fn pretty(&self) -> ::tiny_pretty::Doc<'_> {
+                    match #scrutinee {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "`Pretty` can't be derived for unions",
+            ));
+        }
+    };
+
+    let type_params = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let where_clause = input.generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote! { #param: ::tiny_pretty::Pretty });
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ident = &input.ident;
+    Ok(quote! {
+        impl #impl_generics ::tiny_pretty::Pretty for #ident #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+/// Build doc of a struct or an enum variant.
+/// Each accessor is an expression that evaluates to a reference of the corresponding field.
+fn expand_fields(name: &str, fields: &Fields, accessors: &[TokenStream]) -> Result<TokenStream> {
+    match fields {
+        Fields::Named(..) => {
+            let fields = expand_named_fields(fields, accessors)?;
+            Ok(quote! { ::tiny_pretty::__private::named_fields(#name, #fields) })
+        }
+        Fields::Unnamed(..) => {
+            let docs = fields
+                .iter()
+                .zip(accessors)
+                .map(|(field, accessor)| {
+                    let options = parse_field_options(&field.attrs)?;
+                    if options.flatten {
+                        return Err(Error::new_spanned(
+                            field,
+                            "`flatten` can only be used on named fields",
+                        ));
+                    }
+                    if options.rename.is_some() {
+                        return Err(Error::new_spanned(
+                            field,
+                            "`rename` can only be used on named fields",
+                        ));
+                    }
+                    Ok((!options.skip).then(|| expand_value(&options, accessor)))
+                })
+                .filter_map(Result::transpose)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(quote! {
+                ::tiny_pretty::__private::unnamed_fields(
+                    #name,
+                    ::tiny_pretty::__private::vec![#(#docs),*],
+                )
+            })
+        }
+        Fields::Unit => Ok(quote! { ::tiny_pretty::Doc::text(#name) }),
+    }
+}
+
+/// Build an expression that evaluates to `Vec` of docs of named fields.
+fn expand_named_fields(fields: &Fields, accessors: &[TokenStream]) -> Result<TokenStream> {
+    // Mixed site hygiene prevents the local variable from being captured by user expressions.
+    let docs = Ident::new("__pretty_docs", Span::mixed_site());
+    let statements = fields
+        .iter()
+        .zip(accessors)
+        .map(|(field, accessor)| {
+            let options = parse_field_options(&field.attrs)?;
+            if options.skip {
+                return Ok(quote! {});
+            }
+            if options.flatten {
+                return Ok(quote! {
+                    match ::tiny_pretty::Pretty::__pretty_fields(#accessor) {
+                        ::core::option::Option::Some(docs) => #docs.extend(docs),
+                        ::core::option::Option::None => {
+                            #docs.push(::tiny_pretty::Pretty::pretty(#accessor));
+                        }
+                    }
+                });
+            }
+            let name = match options.rename.clone() {
+                Some(name) => name,
+                None => field
+                    .ident
+                    .as_ref()
+                    .map(|ident| ident.unraw().to_string())
+                    .unwrap_or_default(),
+            };
+            let value = expand_value(&options, accessor);
+            Ok(quote! {
+                #docs.push(::tiny_pretty::__private::field(#name, #value));
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(quote! {
+        {
+            let mut #docs = ::tiny_pretty::__private::vec![];
+            #(#statements)*
+            #docs
+        }
+    })
+}
+
+fn expand_value(options: &FieldOptions, accessor: &TokenStream) -> TokenStream {
+    match &options.with {
+        Some(path) => quote! { #path(#accessor) },
+        None => quote! { ::tiny_pretty::Pretty::pretty(#accessor) },
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    rename: Option<String>,
+    flatten: bool,
+    with: Option<Path>,
+}
+
+fn parse_field_options(attrs: &[Attribute]) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("pretty")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("flatten") {
+                options.flatten = true;
+            } else if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("with") {
+                options.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("unknown `pretty` attribute"));
+            }
+            Ok(())
+        })?;
+        if options.flatten && (options.rename.is_some() || options.with.is_some()) {
+            return Err(Error::new_spanned(
+                attr,
+                "`flatten` can't be used with `rename` or `with`",
+            ));
+        }
+    }
+    Ok(options)
+}
+
+/// Parse `#[pretty(rename = "...")]` of structs, enums and enum variants.
+fn parse_name_options(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("pretty")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown `pretty` attribute"))
+            }
+        })?;
+    }
+    Ok(name)
+}